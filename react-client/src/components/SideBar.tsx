@@ -4,10 +4,11 @@ import { Slider } from "./Slider";
 import Reader from "./Reader";
 
 export const SideBar = () => {
-  const [headerBox, disableHeaderBox, toggleHeaderBox] = useGlobalStore((state) => [
+  const [headerBox, disableHeaderBox, toggleHeaderBox, sniffed] = useGlobalStore((state) => [
     state.headerBox,
     state.disableHeaderBox,
     state.toggleHeaderBox,
+    state.workerApi.sniffed,
   ]);
 
   return (
@@ -15,9 +16,14 @@ export const SideBar = () => {
       <Reader />
       <Slider
         disabled={disableHeaderBox}
-        checked={headerBox}
+        checked={headerBox ?? sniffed?.hasHeader ?? true}
         onChange={() => toggleHeaderBox()}
       />
+      {sniffed && (
+        <span className="sidebar__text">
+          Delimiter "{sniffed.delimiter}", {Math.round(sniffed.confidence * 100)}% sure
+        </span>
+      )}
     </nav>
   );
 };
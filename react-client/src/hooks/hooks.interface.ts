@@ -1,8 +1,9 @@
-import { WorkerRecMessage } from "../worker/worker.interface";
+import { ColumnStats, SniffedDialect, WorkerRecMessage } from "../worker/worker.interface";
 
 export interface Store {
   csvReaderStatus: CsvReaderStatus;
-  headerBox: boolean;
+  /** Set once the user picks, the sniffed header is used until then. */
+  headerBox?: boolean;
   disableHeaderBox: boolean;
   workerApi: WorkerApi;
   setCsvReaderDataStatus: (to: CsvReaderStatus) => void;
@@ -21,4 +22,6 @@ export interface WorkerApi {
   header: string[];
   names: string[];
   equalToOptions: string[];
+  stats: ColumnStats[];
+  sniffed?: SniffedDialect;
 }
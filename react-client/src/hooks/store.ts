@@ -10,15 +10,19 @@ const workerApiDefault = {
   header: [],
   names: [],
   equalToOptions: [],
+  stats: [],
 };
 
 export const useGlobalStore = create<Store>()((set) => ({
   csvReaderStatus: "Empty",
-  headerBox: true,
+  headerBox: undefined,
   disableHeaderBox: false,
   workerApi: workerApiDefault,
   setCsvReaderDataStatus: (to: CsvReaderStatus) => set(() => ({ csvReaderStatus: to })),
-  toggleHeaderBox: () => set((state) => ({ headerBox: !state.headerBox })),
+  toggleHeaderBox: () =>
+    set((state) => ({
+      headerBox: !(state.headerBox ?? state.workerApi.sniffed?.hasHeader ?? true),
+    })),
   toggleDisableHeaderBox: () => set((state) => ({ disableHeaderBox: !state.disableHeaderBox })),
   setSelectedId: (id) => set((state) => ({ workerApi: { ...state.workerApi, selectedId: id } })),
   dispatchWorkerAction: (args: WorkerRecMessage) =>
@@ -30,8 +34,11 @@ const reducer = (state: WorkerApi, action: WorkerRecMessage): WorkerApi => {
     .with({ type: "parsing", payload: P.select() }, ({ progress }) => {
       return { ...state, progress };
     })
+    .with({ type: "sniffed", payload: P.select() }, (sniffed) => {
+      return { ...state, sniffed };
+    })
     .with({ type: "chunk", payload: P.select() }, (payload) => {
-      const slice = payload.map((column) => column.split("DELIMITER_TOKEN"));
+      const slice = payload.map((column) => column.text);
       return { ...state, slice };
     })
     .with({ type: "header", payload: P.select() }, (header) => {
@@ -46,5 +53,8 @@ const reducer = (state: WorkerApi, action: WorkerRecMessage): WorkerApi => {
     .with({ type: "distinct", payload: P.select() }, (payload) => {
       return { ...state, equalToOptions: payload };
     })
+    .with({ type: "describe", payload: P.select() }, (stats) => {
+      return { ...state, stats };
+    })
     .run();
 };
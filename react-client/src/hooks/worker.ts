@@ -103,7 +103,7 @@ export function useParseFileChunk() {
           id: selectedId,
           name: name,
           chunk: value,
-          header: headerBox && progress === 0,
+          header: progress === 0 ? headerBox : undefined,
         },
       };
       wasmWorker.postMessage(action);
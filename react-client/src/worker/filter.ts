@@ -1,4 +1,4 @@
-import { Frame, PollSource } from "../../../wasm-lib";
+import { ColumnSlice, Frame, PollSource } from "../../../wasm-lib";
 
 export default class Source {
   _tag: "source" = "source";
@@ -14,7 +14,7 @@ export default class Source {
     this.name = name;
   }
 
-  slice(frame: Frame, offset: number, len: number) {
+  slice(frame: Frame, offset: number, len: number): ColumnSlice[] {
     return this.source.slice(frame, offset, len);
   }
 }
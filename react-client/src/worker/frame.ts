@@ -1,18 +1,54 @@
-import { Filter, Frame, PollSource } from "../../../wasm-lib";
+import {
+  ColumnSlice,
+  ColumnStats,
+  CsvDialect,
+  Filter,
+  Frame,
+  Histogram,
+  PollSource,
+  Sniffed,
+} from "../../../wasm-lib";
 
 export interface Wasm {
   newFrame: () => Frame;
   processStreamChunk: (
     frame: Frame,
     chunk: Uint8Array,
-    header: boolean,
+    header?: boolean,
+    dialect?: CsvDialect,
   ) => void,
   processStreamTail: (frame: Frame) => void;
+  sniffDialect: (chunk: Uint8Array) => Sniffed;
   addEqualtoFilter: (filter: Filter, frame: Frame, bytes: Uint8Array, column: string) => void;
+  addInFilter: (filter: Filter, frame: Frame, values: string[], column: string) => void;
+  addNullFilter: (filter: Filter, frame: Frame, column: string, isNull: boolean) => void;
+  addBetweenFilter: (
+    filter: Filter,
+    frame: Frame,
+    low: string,
+    high: string,
+    column: string,
+    lowInclusive?: boolean,
+    highInclusive?: boolean
+  ) => void;
   newFilter: () => Filter;
+  filteredHistogram: (
+    filter: Filter,
+    frame: Frame,
+    column: string,
+    bins?: number,
+    binEdges?: Float64Array,
+    strategy?: string
+  ) => Histogram;
   processCommand: (command: string, frame: Frame) => PollSource;
 }
 
+export type HistogramOptions = {
+  bins?: number;
+  binEdges?: number[];
+  strategy?: "sturges" | "fd";
+};
+
 export default class FrameJS {
   _tag: "frame" = "frame";
   private wasm?: Wasm;
@@ -21,15 +57,16 @@ export default class FrameJS {
   readonly id: number;
   readonly name: string;
 
-  constructor(id: number, name: string, wasm: Wasm) {
+  constructor(id: number, name: string, wasm: Wasm, frame?: Frame) {
     this.wasm = wasm;
-    this._frame = this.wasm!.newFrame();
+    this._frame = frame ?? this.wasm!.newFrame();
     this.id = id;
     this.name = name;
   }
 
-  processStreamChunk(chunk: Uint8Array, header: boolean) {
-    return this.wasm!.processStreamChunk(this._frame!, chunk, header);
+  /** `header` and `dialect` are sniffed from the first chunk when left out. */
+  processStreamChunk(chunk: Uint8Array, header?: boolean, dialect?: CsvDialect) {
+    return this.wasm!.processStreamChunk(this._frame!, chunk, header, dialect);
   }
 
   initColumnOrder() {
@@ -46,14 +83,63 @@ export default class FrameJS {
     return this.wasm!.processStreamTail(this._frame!);
   }
 
-  slice(offset: number, len: number) {
+  slice(offset: number, len: number): ColumnSlice[] {
     return this._frame!.slice(offset, len);
   }
 
-  distinct(column: string): string {
+  sortBy(keys: string) {
+    this._frame!.sortBy(keys);
+  }
+
+  clearSort() {
+    this._frame!.clearSort();
+  }
+
+  distinct(column: string): string[] {
     return this._frame!.distinct(column);
   }
 
+  valueCounts(
+    column: string,
+    { limit, sort, normalize }: { limit?: number; sort?: boolean; normalize?: boolean } = {}
+  ): [string | null, number][] {
+    return this._frame!.valueCounts(column, limit, sort, normalize);
+  }
+
+  histogram(
+    column: string,
+    { bins, binEdges, strategy }: HistogramOptions = {},
+    filter?: Filter
+  ): { edges: Float64Array; counts: Uint32Array } {
+    const edges = binEdges && Float64Array.from(binEdges);
+    const histogram = filter
+      ? this.wasm!.filteredHistogram(filter, this._frame!, column, bins, edges, strategy)
+      : this._frame!.histogram(column, bins, edges, strategy);
+    const result = { edges: histogram.edges, counts: histogram.counts };
+    histogram.free();
+    return result;
+  }
+
+  describe(): ColumnStats[] {
+    return this._frame!.describe();
+  }
+
+  setFormula(cell: string, formula: string): string[] {
+    return this._frame!.setFormula(cell, formula);
+  }
+
+  clearFormula(cell: string): string[] {
+    return this._frame!.clearFormula(cell);
+  }
+
+  formula(cell: string): string | undefined {
+    return this._frame!.formula(cell);
+  }
+
+  cellValue(cell: string): number | string | boolean | null {
+    return this._frame!.cellValue(cell);
+  }
+
   get numberOfChunks() {
     return this._frame!.numberOfChunks;
   }
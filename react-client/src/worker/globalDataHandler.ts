@@ -1,6 +1,7 @@
 import { getOrElse, Option, Some } from "fp-ts/lib/Option";
 import { match } from "ts-pattern";
 import Source from "./filter";
+import { ColumnSlice } from "../../../wasm-lib";
 import FrameJS, { Wasm } from "./frame";
 import ListImpl from "./list";
 import {
@@ -50,7 +51,18 @@ export class GlobalDataHandler {
       return frame;
     })(this.data!.find(id) as Option<FrameJS>);
 
-    frame.processStreamChunk(chunk, header);
+    if (frame.numberOfChunks === 0) {
+      const sniffed = this.wasm!.sniffDialect(chunk);
+      const { dialect, hasHeader, confidence } = sniffed;
+      this.worker!.postMessage({
+        type: "sniffed",
+        payload: { delimiter: dialect.delimiter, hasHeader, confidence },
+      });
+      frame.processStreamChunk(chunk, header, dialect);
+      sniffed.free();
+    } else {
+      frame.processStreamChunk(chunk);
+    }
     const progress = frame.numberOfChunks;
 
     this.worker!.postMessage({ type: "parsing", payload: { progress } });
@@ -64,7 +76,12 @@ export class GlobalDataHandler {
         return source.slice(frame.wasmPtr, offset, len);
       })
       .with({ _tag: "frame" }, (frame) => frame.slice(offset, len))
-      .run();
+      .run()
+      .map((column: ColumnSlice) => {
+        const { dtype, values, validity, text } = column;
+        column.free();
+        return { dtype, values, validity, text };
+      });
     this.worker!.postMessage({ type: "chunk", payload: chunk });
   }
 
@@ -77,13 +94,37 @@ export class GlobalDataHandler {
 
   distinct({ id, column }: DistinctSendMessage["payload"]) {
     const frame = this.unsafeGetFrame(id) as FrameJS;
-    const slice = frame.distinct(column).split("DELIMITER_TOKEN");
+    const slice = frame.distinct(column);
     this.worker!.postMessage({ type: "distinct", payload: slice });
   }
 
   processRemainder({ id }: ProcessRemainderSendMessage["payload"]) {
     const frame = this.unsafeGetFrame(id) as FrameJS;
     frame.processStreamTail();
+
+    const stats = frame.describe().map((column) => {
+      const { column: name, dtype, count, nullCount, distinct, mean, std, min } = column;
+      const { q1, median, q3, max, top, minLength, maxLength } = column;
+      column.free();
+      return {
+        column: name,
+        dtype,
+        count,
+        nullCount,
+        distinct,
+        mean,
+        std,
+        min,
+        q1,
+        median,
+        q3,
+        max,
+        top,
+        minLength,
+        maxLength,
+      };
+    });
+    this.worker!.postMessage({ type: "describe", payload: stats });
   }
 
   execCommand({ id, command }: CommandSendMessage["payload"]) {
@@ -100,8 +141,11 @@ export class GlobalDataHandler {
 
     const pollSource = this.wasm!.processCommand(command, frame.wasmPtr);
     const name = frame.name + "_" + pollSource.source_type();
-    const source = new Source(pollSource, id, id + 1, name);
-    this.push(source);
+    if (pollSource.source_type() === "group") {
+      this.push(new FrameJS(id + 1, name, this.wasm!, pollSource.intoFrame()));
+    } else {
+      this.push(new Source(pollSource, id, id + 1, name));
+    }
 
     this.worker!.postMessage({
       type: "addSource",
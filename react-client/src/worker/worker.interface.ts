@@ -14,7 +14,8 @@ export type ParsingSendMessage = {
     id: number;
     name: string;
     chunk: Uint8Array;
-    header: boolean;
+    /** Left out to use the header sniffed from the first chunk. */
+    header?: boolean;
   };
 };
 
@@ -74,11 +75,44 @@ type ParsingRecMessage = {
   };
 };
 
-type ChunkRecMessage = { type: "chunk"; payload: string[] };
+export type ColumnChunk = {
+  dtype: string;
+  values: Int32Array | Float64Array | Uint8Array | string[];
+  validity: Uint8Array;
+  text: string[];
+};
+
+export type ColumnStats = {
+  column: string;
+  dtype: string;
+  count: number;
+  nullCount: number;
+  distinct: number;
+  mean?: number;
+  std?: number;
+  min?: number;
+  q1?: number;
+  median?: number;
+  q3?: number;
+  max?: number;
+  top: [string, number][];
+  minLength?: number;
+  maxLength?: number;
+};
+
+export type SniffedDialect = {
+  delimiter: string;
+  hasHeader: boolean;
+  confidence: number;
+};
+
+type SniffedRecMessage = { type: "sniffed"; payload: SniffedDialect };
+type ChunkRecMessage = { type: "chunk"; payload: ColumnChunk[] };
 type HeaderRecMessage = { type: "header"; payload: string[] };
 type NamesRecMessage = { type: "names"; payload: string[] };
 type SumColRecMessage = { type: "sumCol"; payload: string };
 type DistinctRecMessage = { type: "distinct"; payload: string[] };
+type DescribeRecMessage = { type: "describe"; payload: ColumnStats[] };
 type AddSourceRecMessage = {
   type: "addSource";
   payload: {
@@ -91,7 +125,9 @@ export type WorkerRecMessage =
   | ChunkRecMessage
   | HeaderRecMessage
   | ParsingRecMessage
+  | SniffedRecMessage
   | NamesRecMessage
   | SumColRecMessage
   | DistinctRecMessage
+  | DescribeRecMessage
   | AddSourceRecMessage;
@@ -0,0 +1,220 @@
+use core::cmp::Ordering;
+
+use crate::{
+    column::{Column, SeriesEnum},
+    slice::{ColumnSlice, SliceValues},
+    type_parser::Codes,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregate {
+    Average,
+    Min,
+    Max,
+    Count,
+    Sum,
+    Median,
+}
+
+impl Aggregate {
+    pub fn parse(keyword: &str) -> Option<Self> {
+        match keyword {
+            "Average" => Some(Self::Average),
+            "Min" => Some(Self::Min),
+            "Max" => Some(Self::Max),
+            "Count" => Some(Self::Count),
+            "Sum" => Some(Self::Sum),
+            "Median" => Some(Self::Median),
+            _ => None,
+        }
+    }
+
+    /// Name used in group by functions, `avg(Attack)`.
+    pub fn function(self) -> &'static str {
+        match self {
+            Self::Average => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Median => "median",
+        }
+    }
+
+    pub fn from_function(name: &str) -> Option<Self> {
+        [
+            Self::Average,
+            Self::Min,
+            Self::Max,
+            Self::Count,
+            Self::Sum,
+            Self::Median,
+        ]
+        .into_iter()
+        .find(|aggregate| name.eq_ignore_ascii_case(aggregate.function()))
+    }
+}
+
+/// Result of an aggregate, integers stay exact unless they overflow `i128`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scalar {
+    Null,
+    Int(i128),
+    Float(f64),
+}
+
+impl Scalar {
+    pub fn to_f64(self) -> Option<f64> {
+        match self {
+            Scalar::Null => None,
+            Scalar::Int(value) => Some(value as f64),
+            Scalar::Float(value) => Some(value),
+        }
+    }
+
+    pub fn text(self) -> String {
+        match self {
+            Scalar::Null => String::new(),
+            Scalar::Int(value) => value.to_string(),
+            Scalar::Float(value) => value.to_string(),
+        }
+    }
+
+    /// A single row slice holding the value, integers keep their exact text.
+    pub fn to_slice(self) -> ColumnSlice {
+        let slice = ColumnSlice::new([self.to_f64()], SliceValues::Float64);
+        match self {
+            Scalar::Int(_) => slice
+                .with_dtype(Codes::Int128)
+                .with_formatted(vec![self.text()]),
+            _ => slice.with_dtype(Codes::Float64),
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        match (self, other) {
+            (Scalar::Int(a), Scalar::Int(b)) => a
+                .checked_add(b)
+                .map_or(Scalar::Float(a as f64 + b as f64), Scalar::Int),
+            (a, b) => {
+                Scalar::Float(a.to_f64().unwrap_or_default() + b.to_f64().unwrap_or_default())
+            }
+        }
+    }
+
+    fn is_nan(&self) -> bool {
+        matches!(self, Scalar::Float(value) if value.is_nan())
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Scalar::Int(a), Scalar::Int(b)) => a.cmp(b),
+            (a, b) => a
+                .to_f64()
+                .unwrap_or_default()
+                .total_cmp(&b.to_f64().unwrap_or_default()),
+        }
+    }
+}
+
+/// Aggregates the non null values of a column, an empty selection gives a null
+/// for everything but `Count`. NaN is left out of `Min`, `Max` and `Median`.
+pub fn aggregate(values: impl Iterator<Item = Scalar>, aggregate: Aggregate) -> Scalar {
+    let mut values: Vec<Scalar> = values.collect();
+    let count = values.len();
+    match aggregate {
+        Aggregate::Count => Scalar::Int(count as i128),
+        Aggregate::Sum => values
+            .into_iter()
+            .reduce(Scalar::add)
+            .unwrap_or(Scalar::Null),
+        Aggregate::Average => match values.into_iter().reduce(Scalar::add) {
+            Some(sum) => Scalar::Float(sum.to_f64().unwrap_or_default() / count as f64),
+            None => Scalar::Null,
+        },
+        Aggregate::Min => values
+            .into_iter()
+            .filter(|value| !value.is_nan())
+            .min_by(Scalar::total_cmp)
+            .unwrap_or(Scalar::Null),
+        Aggregate::Max => values
+            .into_iter()
+            .filter(|value| !value.is_nan())
+            .max_by(Scalar::total_cmp)
+            .unwrap_or(Scalar::Null),
+        Aggregate::Median => {
+            values.retain(|value| !value.is_nan());
+            values.sort_by(Scalar::total_cmp);
+            let mid = values.len() / 2;
+            match values.len() {
+                0 => Scalar::Null,
+                len if len % 2 == 1 => values[mid],
+                _ => {
+                    let low = values[mid - 1].to_f64().unwrap_or_default();
+                    let high = values[mid].to_f64().unwrap_or_default();
+                    Scalar::Float(low + (high - low) / 2.0)
+                }
+            }
+        }
+    }
+}
+
+/// Integers get the narrowest integer type holding all of them, a single float makes it a float column.
+pub fn scalar_column(name: String, values: Vec<Scalar>) -> Column {
+    if values.iter().any(|value| matches!(value, Scalar::Float(_))) {
+        let series = values.iter().map(|value| value.to_f64()).collect();
+        return Column::new(SeriesEnum::F64(Box::new(series)), name, Codes::Float64);
+    }
+
+    let ints: Vec<Option<i128>> = values
+        .iter()
+        .map(|value| match value {
+            Scalar::Int(value) => Some(*value),
+            _ => None,
+        })
+        .collect();
+    let fits = |fits: fn(i128) -> bool| ints.iter().flatten().all(|&value| fits(value));
+    if fits(|value| i32::try_from(value).is_ok()) {
+        let series = ints.iter().map(|el| el.and_then(|x| i32::try_from(x).ok()));
+        Column::new(
+            SeriesEnum::I32(Box::new(series.collect())),
+            name,
+            Codes::Int32,
+        )
+    } else if fits(|value| i64::try_from(value).is_ok()) {
+        let series = ints.iter().map(|el| el.and_then(|x| i64::try_from(x).ok()));
+        Column::new(
+            SeriesEnum::I64(Box::new(series.collect())),
+            name,
+            Codes::Int64,
+        )
+    } else {
+        Column::new(SeriesEnum::I128(Box::new(ints)), name, Codes::Int128)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{aggregate, Aggregate, Scalar};
+
+    #[test]
+    fn aggregates() {
+        let ints = || [3, 1, 4, 1].into_iter().map(Scalar::Int);
+        assert_eq!(aggregate(ints(), Aggregate::Sum), Scalar::Int(9));
+        assert_eq!(aggregate(ints(), Aggregate::Count), Scalar::Int(4));
+        assert_eq!(aggregate(ints(), Aggregate::Min), Scalar::Int(1));
+        assert_eq!(aggregate(ints(), Aggregate::Max), Scalar::Int(4));
+        assert_eq!(aggregate(ints(), Aggregate::Average), Scalar::Float(2.25));
+        assert_eq!(aggregate(ints(), Aggregate::Median), Scalar::Float(2.0));
+
+        let floats = [2.5, f64::NAN, 0.5, 1.0].into_iter().map(Scalar::Float);
+        assert_eq!(aggregate(floats, Aggregate::Median), Scalar::Float(1.0));
+        assert_eq!(aggregate([].into_iter(), Aggregate::Max), Scalar::Null);
+
+        let wide = [i128::MAX, 1].into_iter().map(Scalar::Int);
+        assert_eq!(
+            aggregate(wide, Aggregate::Sum),
+            Scalar::Float(i128::MAX as f64 + 1.0)
+        );
+    }
+}
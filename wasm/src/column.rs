@@ -1,11 +1,21 @@
-use bitvec::slice::BitSlice;
+use std::collections::HashMap;
+
+use bitvec::prelude::BitVec;
+use chrono::{NaiveDate, NaiveTime};
 
 use crate::{
+    aggregate::{Aggregate, Scalar},
+    describe::{describe, ColumnStats},
+    diagnostics::CastReport,
+    filter::Comparison,
+    number_format::{NumberFormat, NumericDisplay},
     series::{
-        errors::{FilterResult, NonHashable},
-        SeriesTrait,
+        errors::{FilterResult, NonHashable, WrongType},
+        temporal::{TemporalSeries, Timestamp},
+        NullSeries, SeriesTrait,
     },
-    type_parser::Codes,
+    slice::ColumnSlice,
+    type_parser::{infer_bytes, Codes},
     Words,
 };
 
@@ -13,6 +23,41 @@ pub struct Column {
     series: Box<dyn SeriesTrait>,
     name: String,
     dtype: Codes,
+    display: Option<NumericDisplay>,
+}
+
+/// Column types fixed by the user, a type given by name wins over one given by position.
+#[derive(Clone, Debug, Default)]
+pub struct Schema {
+    names: HashMap<String, Codes>,
+    indices: HashMap<usize, Codes>,
+    formats: HashMap<Codes, String>,
+}
+
+impl Schema {
+    pub fn set_by_name(&mut self, name: &str, dtype: Codes) {
+        self.names.insert(name.into(), dtype);
+    }
+
+    pub fn set_by_index(&mut self, index: usize, dtype: Codes) {
+        self.indices.insert(index, dtype);
+    }
+
+    pub fn get(&self, index: usize, name: &str) -> Option<Codes> {
+        self.names
+            .get(name)
+            .or_else(|| self.indices.get(&index))
+            .copied()
+    }
+
+    pub fn set_format(&mut self, dtype: Codes, format: &str) {
+        self.formats.insert(dtype, format.into());
+    }
+
+    /// Format used to read and write the values of temporal columns.
+    pub fn format(&self, dtype: Codes) -> Option<&str> {
+        self.formats.get(&dtype).map(String::as_str)
+    }
 }
 
 pub enum SeriesEnum {
@@ -22,54 +67,81 @@ pub enum SeriesEnum {
     F32(Box<Vec<Option<f32>>>),
     F64(Box<Vec<Option<f64>>>),
     Bool(Box<Vec<Option<bool>>>),
+    Date(Box<TemporalSeries<NaiveDate>>),
+    Time(Box<TemporalSeries<NaiveTime>>),
+    Timestamp(Box<TemporalSeries<Timestamp>>),
     Any(Box<Vec<Option<String>>>),
+    Null(Box<NullSeries>),
 }
 
 impl Column {
     pub fn new(buffer: SeriesEnum, name: String, dtype: Codes) -> Self {
-        match buffer {
-            SeriesEnum::I32(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::I64(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::I128(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::F32(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::F64(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::Bool(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::Any(series) => Self {
-                series,
-                name,
-                dtype,
-            },
+        let series: Box<dyn SeriesTrait> = match buffer {
+            SeriesEnum::I32(series) => series,
+            SeriesEnum::I64(series) => series,
+            SeriesEnum::I128(series) => series,
+            SeriesEnum::F32(series) => series,
+            SeriesEnum::F64(series) => series,
+            SeriesEnum::Bool(series) => series,
+            SeriesEnum::Date(series) => series,
+            SeriesEnum::Time(series) => series,
+            SeriesEnum::Timestamp(series) => series,
+            SeriesEnum::Any(series) => series,
+            SeriesEnum::Null(series) => series,
+        };
+
+        Self {
+            series,
+            name,
+            dtype,
+            display: None,
         }
     }
 
+    pub fn empty(name: String, dtype: Codes, format: Option<&str>) -> Self {
+        let buffer = match dtype {
+            Codes::Boolean => SeriesEnum::Bool(Box::default()),
+            Codes::Int32 => SeriesEnum::I32(Box::default()),
+            Codes::Int64 => SeriesEnum::I64(Box::default()),
+            Codes::Int128 => SeriesEnum::I128(Box::default()),
+            Codes::Float32 => SeriesEnum::F32(Box::default()),
+            Codes::Float64 => SeriesEnum::F64(Box::default()),
+            Codes::Date => SeriesEnum::Date(Box::new(TemporalSeries::new(Vec::new(), format))),
+            Codes::Time => SeriesEnum::Time(Box::new(TemporalSeries::new(Vec::new(), format))),
+            Codes::Timestamp => {
+                SeriesEnum::Timestamp(Box::new(TemporalSeries::new(Vec::new(), format)))
+            }
+            Codes::Null => SeriesEnum::Null(Box::default()),
+            _ => return Self::new(SeriesEnum::Any(Box::default()), name, Codes::Any),
+        };
+        Self::new(buffer, name, dtype)
+    }
+
+    /// A column of `len` nulls, typed by the first values appended to it.
+    pub fn nulls(name: String, len: usize) -> Self {
+        Self::new(
+            SeriesEnum::Null(Box::new(NullSeries(len))),
+            name,
+            Codes::Null,
+        )
+    }
+
     pub fn len(&self) -> usize {
         self.series.len()
     }
 
+    pub fn null_count(&self) -> usize {
+        self.series.null_count()
+    }
+
+    pub fn null_mask(&self) -> BitVec {
+        self.series.null_mask()
+    }
+
+    pub fn valid_count(&self) -> usize {
+        self.len() - self.null_count()
+    }
+
     pub fn series(&self) -> &dyn SeriesTrait {
         self.series.as_ref()
     }
@@ -78,12 +150,154 @@ impl Column {
         self.series.is_empty()
     }
 
-    pub fn extend_from_words(&mut self, bytes: Words) {
-        self.series.extend_from_words(bytes)
+    /// Appends the words, first widening the column when some of them cannot be
+    /// represented by its current type. Returns the previous type on promotion.
+    pub fn extend_from_words(&mut self, bytes: Words, numbers: &NumberFormat) -> Option<Codes> {
+        if !self.dtype.is_numeric() {
+            self.series.settle(&bytes);
+            let code = self.widest(&bytes);
+            let previous = self.promote(code);
+            self.series.settle(&bytes);
+            self.series.extend_from_words(bytes);
+            return previous;
+        }
+
+        let (normalized, display) = numbers.normalize_words(&bytes);
+        let code = self.widest(&normalized);
+        let previous = self.promote(code);
+
+        if self.dtype.is_numeric() {
+            self.display = self.display.take().or(display);
+            self.series.extend_from_words(normalized);
+        } else {
+            self.series.extend_from_words(bytes);
+        }
+        previous
+    }
+
+    /// Appends the values of another column, first widening the column to the type of both.
+    pub fn append(&mut self, other: &Column) {
+        self.promote(self.dtype.promote(other.dtype));
+        self.series.extend_from_words(other.series.words());
+    }
+
+    /// Appends the words keeping the current type, values that do not fit become nulls.
+    pub fn extend_exact(&mut self, bytes: Words, numbers: &NumberFormat) {
+        if self.dtype.is_numeric() {
+            let (normalized, display) = numbers.normalize_words(&bytes);
+            self.display = self.display.take().or(display);
+            self.series.extend_from_words(normalized);
+        } else {
+            self.series.settle(&bytes);
+            self.series.extend_from_words(bytes);
+        }
+    }
+
+    pub fn cast(
+        &self,
+        dtype: Codes,
+        format: Option<&str>,
+        numbers: &NumberFormat,
+    ) -> (Self, CastReport) {
+        let mut report = CastReport::new(&self.name, self.dtype, dtype);
+        let mut column = Self::empty(self.name.clone(), dtype, format);
+        let (words, normalized) = if dtype.is_numeric() {
+            column.display = self.display.clone();
+            let words = self.series.words();
+            let normalized = numbers.normalize_words(&words).0;
+            (words, normalized)
+        } else {
+            let words = self.display_words();
+            (words.clone(), words)
+        };
+
+        words
+            .into_iter()
+            .zip(&normalized)
+            .enumerate()
+            .filter(|(_, (_, plain))| !column.series.fits(plain))
+            .for_each(|(row, (word, _))| report.push(row, word));
+
+        column.extend_exact(words, numbers);
+        (column, report)
+    }
+
+    /// Smallest type able to hold the current values along with the words. Words of the
+    /// column's type it still cannot read, like a date in another convention, make it text.
+    fn widest(&self, bytes: &Words) -> Codes {
+        bytes
+            .into_iter()
+            .filter(|word| !self.series.fits(word))
+            .fold(self.dtype, |code, word| {
+                match code.promote(infer_bytes(word)) {
+                    code if code == self.dtype => Codes::Any,
+                    code => code,
+                }
+            })
     }
 
-    pub fn join(&self, offset: usize, size: usize) -> String {
-        self.series.join(offset, size)
+    fn promote(&mut self, code: Codes) -> Option<Codes> {
+        if code == self.dtype {
+            return None;
+        }
+
+        let buffer = match (&self.display, code) {
+            (Some(_), Codes::Any) => {
+                let words = self.display_words();
+                let series = words.into_iter().map(|word| match word {
+                    [] => None,
+                    word => String::from_utf8(word.into()).ok(),
+                });
+                SeriesEnum::Any(Box::new(series.collect()))
+            }
+            _ => self.series.promote(code)?,
+        };
+        let previous = self.dtype;
+        let display = self.display.take().filter(|_| code.is_numeric());
+        *self = Self::new(buffer, std::mem::take(&mut self.name), code);
+        self.display = display;
+        Some(previous)
+    }
+
+    /// Values as text, numbers are written the way they were read.
+    fn display_words(&self) -> Words {
+        let words = self.series.words();
+        let display = match &self.display {
+            Some(display) => display,
+            None => return words,
+        };
+
+        let mut ret = Words::default();
+        for word in &words {
+            let word = String::from_utf8_lossy(word);
+            ret.extend(display.render(&word).as_bytes());
+        }
+        ret
+    }
+
+    /// The values at the given rows, numbers are written the way they were read.
+    pub fn slice(&self, rows: &[usize]) -> ColumnSlice {
+        let slice = self.series.take(rows).with_dtype(self.dtype);
+        match &self.display {
+            Some(display) => {
+                let formatted = slice
+                    .text()
+                    .iter()
+                    .map(|plain| display.render(plain))
+                    .collect();
+                slice.with_formatted(formatted)
+            }
+            None => slice,
+        }
+    }
+
+    pub fn range(&self, offset: usize, size: usize) -> ColumnSlice {
+        let end = self.len().min(offset.saturating_add(size));
+        self.slice(&(offset.min(end)..end).collect::<Vec<_>>())
+    }
+
+    pub fn text(&self, offset: usize, size: usize) -> Vec<String> {
+        self.range(offset, size).text()
     }
 
     pub fn sum(&self) -> Result<Self, &str> {
@@ -93,11 +307,35 @@ impl Column {
             series,
             name,
             dtype: self.dtype,
+            display: self.display.clone(),
         })
     }
 
+    pub fn describe(&self) -> ColumnStats {
+        describe(self)
+    }
+
+    /// The given rows as a new column of the same type.
+    pub fn gather(&self, rows: &[usize]) -> Self {
+        let mut column = Self::new(self.series.gather(rows), self.name.clone(), self.dtype);
+        column.display = self.display.clone();
+        column
+    }
+
+    /// Aggregate over the given rows, every column can be counted.
+    pub fn aggregate(&self, rows: &[usize], aggregate: Aggregate) -> Result<Scalar, WrongType> {
+        match aggregate {
+            Aggregate::Count => {
+                let nulls = self.null_mask();
+                let count = rows.iter().filter(|&&row| !nulls[row]).count();
+                Ok(Scalar::Int(count as i128))
+            }
+            _ => self.series.aggregate(rows, aggregate),
+        }
+    }
+
     pub fn first(&self) -> String {
-        self.series.join(0, 1)
+        self.text(0, 1).pop().unwrap_or_default()
     }
 
     pub fn name(&self) -> &str {
@@ -108,22 +346,47 @@ impl Column {
         self.dtype
     }
 
-    pub fn equal_to(&self, other: &dyn SeriesTrait) -> FilterResult {
-        self.series.equal_to(other)
+    pub fn equal_to(&self, other: &dyn SeriesTrait, tolerance: f64) -> FilterResult<'_> {
+        self.series.equal_to_within(other, tolerance)
+    }
+
+    pub fn compare(&self, other: &dyn SeriesTrait, op: Comparison) -> FilterResult<'_> {
+        self.series.compare(other, op)
     }
 
-    pub fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String {
-        self.series.filter_join(mask, offset, size)
+    pub fn distinct(&self) -> Result<Vec<String>, NonHashable> {
+        let distinct = self.series.distinct()?;
+        Ok(match &self.display {
+            Some(display) => distinct.iter().map(|plain| display.render(plain)).collect(),
+            None => distinct,
+        })
     }
 
-    pub fn distinct(&self) -> Result<String, NonHashable> {
-        self.series.distinct()
+    /// Values with their number of rows, nulls counted as their own bucket. Sorted puts the
+    /// most frequent first, ties keep the order in which the values first appear.
+    pub fn value_counts(
+        &self,
+        limit: Option<usize>,
+        sort: bool,
+    ) -> Result<Vec<(Option<String>, usize)>, NonHashable> {
+        let mut counts = self.series.value_counts()?;
+        if sort {
+            counts.sort_by_key(|&(_, count)| core::cmp::Reverse(count));
+        }
+        counts.truncate(limit.unwrap_or(usize::MAX));
+        if let Some(display) = &self.display {
+            counts
+                .iter_mut()
+                .filter_map(|(value, _)| value.as_mut())
+                .for_each(|value| *value = display.render(value));
+        }
+        Ok(counts)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::type_parser::Codes;
+    use crate::{number_format::NumberFormat, type_parser::Codes, Words};
 
     use super::{Column, SeriesEnum};
 
@@ -136,4 +399,137 @@ mod test {
 
         assert_eq!(first, "1".to_string());
     }
+
+    #[test]
+    fn promote() {
+        let numbers = NumberFormat::default();
+        let mut words = Words::default();
+        ["4", "", "3.5"]
+            .iter()
+            .for_each(|w| words.extend(w.as_bytes()));
+
+        let series = SeriesEnum::I32(Box::new(vec![Some(1), None]));
+        let mut column = Column::new(series, "_".into(), Codes::Int32);
+
+        assert_eq!(
+            column.extend_from_words(words.clone(), &numbers),
+            Some(Codes::Int32)
+        );
+        assert_eq!(column.dtype(), Codes::Float64);
+        assert_eq!(column.len(), 5);
+        assert_eq!(column.first(), "1".to_string());
+        assert_eq!(column.extend_from_words(words, &numbers), None);
+
+        let mut words = Words::default();
+        words.extend(b"N/A");
+        assert_eq!(
+            column.extend_from_words(words, &numbers),
+            Some(Codes::Float64)
+        );
+        assert_eq!(column.dtype(), Codes::Any);
+        assert_eq!(column.len(), 9);
+    }
+
+    #[test]
+    fn sparse() {
+        let numbers = NumberFormat::default();
+        let words = |values: &[&str]| {
+            let mut words = Words::default();
+            values.iter().for_each(|w| words.extend(w.as_bytes()));
+            words
+        };
+
+        let mut column = Column::empty("_".into(), Codes::Null, None);
+        assert_eq!(column.extend_from_words(words(&["", ""]), &numbers), None);
+        assert_eq!((column.dtype(), column.null_count()), (Codes::Null, 2));
+
+        let promoted = column.extend_from_words(words(&["25/12/2020", ""]), &numbers);
+        assert_eq!(promoted, Some(Codes::Null));
+        assert_eq!(column.dtype(), Codes::Date);
+        assert_eq!(column.text(0, 4), ["", "", "2020-12-25", ""]);
+
+        let mut column = Column::empty("_".into(), Codes::Null, None);
+        column.extend_from_words(words(&[""]), &numbers);
+        column.extend_from_words(words(&["130"]), &numbers);
+        assert_eq!(column.dtype(), Codes::Int32);
+        assert_eq!(column.text(0, 2), ["", "130"]);
+    }
+
+    #[test]
+    fn date_conventions() {
+        let numbers = NumberFormat::default();
+        let words = |values: &[&str]| {
+            let mut words = Words::default();
+            values.iter().for_each(|w| words.extend(w.as_bytes()));
+            words
+        };
+
+        let mut column = Column::empty("_".into(), Codes::Date, None);
+        column.extend_from_words(words(&["01/02/2020", "25/12/2020", "2020-03-04"]), &numbers);
+        assert_eq!(column.dtype(), Codes::Date);
+        assert_eq!(
+            column.text(0, 3),
+            ["2020-02-01", "2020-12-25", "2020-03-04"]
+        );
+
+        let promoted = column.extend_from_words(words(&["12/25/2020"]), &numbers);
+        assert_eq!(promoted, Some(Codes::Date));
+        assert_eq!(column.dtype(), Codes::Any);
+        assert_eq!(column.text(3, 1), ["12/25/2020"]);
+
+        let mut column = Column::empty("_".into(), Codes::Date, Some("%d.%m.%Y"));
+        column.extend_exact(words(&["25.12.2020", "2020-12-25"]), &numbers);
+        assert_eq!(column.text(0, 2), ["25.12.2020", ""]);
+    }
+
+    #[test]
+    fn equal_to_floats() {
+        let series = SeriesEnum::F64(Box::new(vec![Some(0.1 + 0.2), Some(f64::NAN), None]));
+        let column = Column::new(series, "_".into(), Codes::Float64);
+
+        let needle = vec![Some(0.3)];
+        let mask = column.equal_to(&needle, 0.0).unwrap();
+        assert!(mask.not_any());
+        let mask = column.equal_to(&needle, 1e-9).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0]);
+
+        let needle = vec![Some(f64::NAN)];
+        let mask = column.equal_to(&needle, 1.0).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn value_counts() {
+        let values = ["Fire", "Water", "", "Fire", "Grass", "", "Water", "Fire"];
+        let series = SeriesEnum::Any(Box::new(
+            values
+                .iter()
+                .map(|value| (!value.is_empty()).then(|| value.to_string()))
+                .collect(),
+        ));
+        let column = Column::new(series, "Type".into(), Codes::Any);
+
+        let counts = column.value_counts(None, true).unwrap();
+        let fire = (Some("Fire".to_string()), 3);
+        assert_eq!(counts[0], fire);
+        assert_eq!(counts[1], (Some("Water".to_string()), 2));
+        assert_eq!(counts[2], (None, 2));
+        assert_eq!(counts[3], (Some("Grass".to_string()), 1));
+
+        let counts = column.value_counts(Some(2), false).unwrap();
+        assert_eq!(counts, [fire, (Some("Water".to_string()), 2)]);
+        assert_eq!(column.distinct().unwrap(), ["Fire", "Water", "", "Grass"]);
+
+        let series = SeriesEnum::F64(Box::new(vec![Some(1.5), None, Some(f64::NAN), Some(1.5)]));
+        let column = Column::new(series, "_".into(), Codes::Float64);
+        let counts = column.value_counts(None, true).unwrap();
+        assert_eq!(
+            counts,
+            [
+                (Some("1.5".to_string()), 2),
+                (None, 1),
+                (Some("NaN".to_string()), 1)
+            ]
+        );
+    }
 }
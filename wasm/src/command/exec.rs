@@ -1,18 +1,506 @@
 use super::parser::{parse_command, ParsedCommand};
-use crate::{filter::Filter, Frame};
+use crate::{
+    aggregate::Scalar,
+    compute::Expr,
+    filter::{Comparison, Filter, FilterError, TextPredicate},
+    sort::SortKey,
+    Frame,
+};
 
 pub enum Slice {
     FilterSlice(Filter),
+    /// Keys to order the frame by, applied to the frame itself.
+    SortSlice(Vec<SortKey>),
+    AggregateSlice(Scalar),
+    /// A new frame with a row per group.
+    GroupSlice(Box<Frame>),
+    /// Column to compute, added to the frame itself.
+    AddColumnSlice(String, Expr),
 }
 
 pub fn exec(input: &str, frame: &Frame) -> Result<Slice, &'static str> {
     let (_, command) = parse_command(input).map_err(|_| "Cannot parse command")?;
     match command {
+        ParsedCommand::Sort(keys) => Ok(Slice::SortSlice(keys)),
+        ParsedCommand::AddColumn(name, expr) => Ok(Slice::AddColumnSlice(name.into(), expr)),
+        ParsedCommand::GroupBy(keys, aggregates) => {
+            let keys: Vec<String> = keys.into_iter().map(String::from).collect();
+            let frame = frame
+                .group_by(&keys, &aggregates)
+                .map_err(|_| "Cannot group frame")?;
+            Ok(Slice::GroupSlice(Box::new(frame)))
+        }
+        ParsedCommand::Aggregate(aggregate, column, condition) => {
+            let column = frame
+                .columns
+                .iter()
+                .find(|col| col.name() == column)
+                .ok_or("Unknown column")?;
+            let rows = match condition {
+                Some(condition) => evaluate(&condition, frame)?.rows(frame, 0, usize::MAX),
+                None => (0..column.len()).collect(),
+            };
+            let value = column
+                .aggregate(&rows, aggregate)
+                .map_err(|_| "Cannot aggregate column")?;
+            Ok(Slice::AggregateSlice(value))
+        }
+        predicate => Ok(Slice::FilterSlice(evaluate(&predicate, frame)?)),
+    }
+}
+
+/// Builds the filter of a predicate tree, one mask per condition.
+fn evaluate(command: &ParsedCommand, frame: &Frame) -> Result<Filter, &'static str> {
+    match *command {
         ParsedCommand::EqualFilter(column, value) => {
             let mut filter = Filter::default();
-            filter.add_equalto_filter(frame, value.as_bytes(), column);
-            Ok(Slice::FilterSlice(filter))
+            filter
+                .add_equalto_filter(frame, value.as_bytes(), column)
+                .map_err(|err| reason(err, "Cannot compare column"))?;
+            Ok(filter)
+        }
+        ParsedCommand::LessFilter(column, value) => compare(frame, column, value, Comparison::Less),
+        ParsedCommand::LessEqualFilter(column, value) => {
+            compare(frame, column, value, Comparison::LessEqual)
+        }
+        ParsedCommand::GreaterFilter(column, value) => {
+            compare(frame, column, value, Comparison::Greater)
+        }
+        ParsedCommand::GreaterEqualFilter(column, value) => {
+            compare(frame, column, value, Comparison::GreaterEqual)
+        }
+        ParsedCommand::NotEqualFilter(column, value) => {
+            compare(frame, column, value, Comparison::NotEqual)
+        }
+        ParsedCommand::InFilter(column, ref values) => {
+            let values: Vec<&[u8]> = values.iter().map(|value| value.as_bytes()).collect();
+            let mut filter = Filter::default();
+            filter
+                .add_in_filter(frame, &values, column)
+                .map_err(|err| reason(err, "Cannot compare column"))?;
+            Ok(filter)
+        }
+        ParsedCommand::BetweenFilter(column, low, high, inclusive) => {
+            let mut filter = Filter::default();
+            filter
+                .add_between_filter(
+                    frame,
+                    low.as_bytes(),
+                    high.as_bytes(),
+                    column,
+                    inclusive,
+                    inclusive,
+                )
+                .map_err(|err| reason(err, "Cannot compare column"))?;
+            Ok(filter)
+        }
+        ParsedCommand::ContainsFilter(column, value) => {
+            text(frame, column, TextPredicate::Contains(value.into()))
+        }
+        ParsedCommand::StartsWithFilter(column, value) => {
+            text(frame, column, TextPredicate::StartsWith(value.into()))
+        }
+        ParsedCommand::EndsWithFilter(column, value) => {
+            text(frame, column, TextPredicate::EndsWith(value.into()))
+        }
+        ParsedCommand::MatchesFilter(column, pattern) => {
+            let predicate = TextPredicate::regex(pattern).map_err(|_| "Invalid pattern")?;
+            text(frame, column, predicate)
+        }
+        ParsedCommand::ILikeFilter(column, pattern) => {
+            let predicate = TextPredicate::ilike(pattern).map_err(|_| "Invalid pattern")?;
+            text(frame, column, predicate)
+        }
+        ParsedCommand::IsNullFilter(column) => {
+            let mut filter = Filter::default();
+            filter
+                .add_null_filter(frame, column, true)
+                .map_err(|err| reason(err, "Unknown column"))?;
+            Ok(filter)
+        }
+        ParsedCommand::IsNotNullFilter(column) => {
+            let mut filter = Filter::default();
+            filter
+                .add_null_filter(frame, column, false)
+                .map_err(|err| reason(err, "Unknown column"))?;
+            Ok(filter)
+        }
+        ParsedCommand::And(ref left, ref right) => {
+            Ok(evaluate(left, frame)? & evaluate(right, frame)?)
         }
-        _ => unimplemented!(""),
+        ParsedCommand::Or(ref left, ref right) => {
+            Ok(evaluate(left, frame)? | evaluate(right, frame)?)
+        }
+        ParsedCommand::Not(ref inner) => negate(inner, frame),
+        ParsedCommand::Sort(_)
+        | ParsedCommand::GroupBy(..)
+        | ParsedCommand::AddColumn(..)
+        | ParsedCommand::Aggregate(..) => Err("Not a filter"),
+    }
+}
+
+/// Builds the filter of the rows where a predicate is false. As in SQL, a condition on
+/// a null is unknown rather than false, so `NOT (HP > 50)` leaves out the nulls like
+/// `HP <= 50` does.
+fn negate(command: &ParsedCommand, frame: &Frame) -> Result<Filter, &'static str> {
+    match *command {
+        ParsedCommand::And(ref left, ref right) => Ok(negate(left, frame)? | negate(right, frame)?),
+        ParsedCommand::Or(ref left, ref right) => Ok(negate(left, frame)? & negate(right, frame)?),
+        ParsedCommand::Not(ref inner) => evaluate(inner, frame),
+        ParsedCommand::IsNullFilter(column) => {
+            evaluate(&ParsedCommand::IsNotNullFilter(column), frame)
+        }
+        ParsedCommand::IsNotNullFilter(column) => {
+            evaluate(&ParsedCommand::IsNullFilter(column), frame)
+        }
+        ParsedCommand::EqualFilter(column, _)
+        | ParsedCommand::LessFilter(column, _)
+        | ParsedCommand::LessEqualFilter(column, _)
+        | ParsedCommand::GreaterFilter(column, _)
+        | ParsedCommand::GreaterEqualFilter(column, _)
+        | ParsedCommand::NotEqualFilter(column, _)
+        | ParsedCommand::InFilter(column, _)
+        | ParsedCommand::BetweenFilter(column, ..)
+        | ParsedCommand::ContainsFilter(column, _)
+        | ParsedCommand::StartsWithFilter(column, _)
+        | ParsedCommand::EndsWithFilter(column, _)
+        | ParsedCommand::MatchesFilter(column, _)
+        | ParsedCommand::ILikeFilter(column, _) => {
+            let known = evaluate(&ParsedCommand::IsNotNullFilter(column), frame)?;
+            Ok(!evaluate(command, frame)? & known)
+        }
+        ParsedCommand::Sort(_)
+        | ParsedCommand::GroupBy(..)
+        | ParsedCommand::AddColumn(..)
+        | ParsedCommand::Aggregate(..) => Err("Not a filter"),
+    }
+}
+
+/// Message of a filter error, `wrong_type` tells what the column is missing.
+fn reason(err: FilterError, wrong_type: &'static str) -> &'static str {
+    match err {
+        FilterError::Schema(_) => "Unknown column",
+        FilterError::WrongType => wrong_type,
+    }
+}
+
+fn compare(
+    frame: &Frame,
+    column: &str,
+    value: &str,
+    op: Comparison,
+) -> Result<Filter, &'static str> {
+    let mut filter = Filter::default();
+    filter
+        .add_comparison_filter(frame, value.as_bytes(), column, op)
+        .map_err(|err| reason(err, "Cannot compare column"))?;
+    Ok(filter)
+}
+
+fn text(frame: &Frame, column: &str, predicate: TextPredicate) -> Result<Filter, &'static str> {
+    let mut filter = Filter::default();
+    filter
+        .add_text_filter(frame, column, &predicate)
+        .map_err(|err| reason(err, "Not a text column"))?;
+    Ok(filter)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{exec, Slice};
+    use crate::{
+        aggregate::Scalar, diagnostics::SchemaError, sort::SortKey, type_parser::Codes, Frame,
+    };
+
+    fn rows(input: &str, frame: &Frame) -> Vec<usize> {
+        match exec(input, frame).unwrap() {
+            Slice::FilterSlice(filter) => filter.rows(frame, 0, usize::MAX),
+            Slice::SortSlice(_) => frame.index().to_vec(),
+            Slice::AddColumnSlice(..) => frame.index().to_vec(),
+            Slice::AggregateSlice(_) | Slice::GroupSlice(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn comparison_filters() {
+        let bytes = "Name,Attack,Weight,Legendary\nFlareon,130,25.0,false\nJolteon,,24.5,true\nEspeon,65,26.5,false\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(rows("Filter Attack > 65", &frame), vec![0]);
+        assert_eq!(rows("Filter Attack >= 65", &frame), vec![0, 2]);
+        assert_eq!(rows("Filter Attack != 65", &frame), vec![0]);
+        assert_eq!(rows("Filter Weight < 25", &frame), vec![1]);
+        assert_eq!(rows("Filter Weight <= 25", &frame), vec![0, 1]);
+        assert_eq!(rows("Filter Name < Flareon", &frame), vec![2]);
+        assert_eq!(rows("Filter Name >= Flareon", &frame), vec![0, 1]);
+        assert_eq!(rows("Filter Legendary > false", &frame), vec![1]);
+        assert_eq!(
+            exec("Filter Nope > 3", &frame).err(),
+            Some("Unknown column")
+        );
+        assert_eq!(
+            exec("Filter Nope IS NULL", &frame).err(),
+            Some("Unknown column")
+        );
+    }
+
+    #[test]
+    fn boolean_filters() {
+        let bytes = "Name,Type 1,Legendary\nFlareon,Fire,false\nVaporeon,Water,false\nMoltres,Fire,true\nSuicune,Water,true\nJolteon,Electric,false\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let input = "Filter (Type 1 = Fire OR Type 1 = Water) AND NOT Legendary = true";
+        assert_eq!(rows(input, &frame), vec![0, 1]);
+        let input = "Filter Type 1 = Electric OR Type 1 = Fire AND Legendary = true";
+        assert_eq!(rows(input, &frame), vec![2, 4]);
+        assert_eq!(rows("Filter NOT (Name < N)", &frame), vec![1, 3]);
+    }
+
+    #[test]
+    fn equality_filters() {
+        let bytes = "Name,Weight,Steps,Id,Legendary,Found\nFlareon,25.1,3000000000,170141183460469231731687303715884105727,false,12/25/2020\nMoltres,60.05,5120,1,true,2021-01-02\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(rows("Filter Weight = 25.1", &frame), vec![0]);
+        assert!(rows("Filter Weight = 60.1", &frame).is_empty());
+        frame.set_float_tolerance(0.1);
+        assert_eq!(rows("Filter Weight = 60.1", &frame), vec![1]);
+        assert_eq!(rows("Filter Steps = 3000000000", &frame), vec![0]);
+        assert_eq!(
+            rows(
+                "Filter Id = 170141183460469231731687303715884105727",
+                &frame
+            ),
+            vec![0]
+        );
+        assert_eq!(rows("Filter Legendary = true", &frame), vec![1]);
+        assert_eq!(rows("Filter Found = 2020-12-25", &frame), vec![0]);
+        assert_eq!(rows("Filter Found > 2020-12-25", &frame), vec![1]);
+
+        let bytes = "Name,Attack\nFlareon,130\nJolteon,\n,65\n";
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert!(rows("Filter Attack = abc", &frame).is_empty());
+        assert_eq!(rows("Filter Attack = [abc, 130]", &frame), vec![0]);
+        assert!(rows("Filter Name = \"\"", &frame).is_empty());
+    }
+
+    #[test]
+    fn in_and_between_filters() {
+        let bytes = "Name,Type,Attack\nFlareon,Fire,130\nVaporeon,Water,65\nLeafeon,Grass,110\nJolteon,Electric,65\nEevee,Normal,55\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(
+            rows("Filter Type = [Fire, Water, Grass]", &frame),
+            vec![0, 1, 2]
+        );
+        assert_eq!(rows("Filter Attack = [55, 130]", &frame), vec![0, 4]);
+        assert_eq!(
+            rows("Filter Attack BETWEEN 65 AND 110", &frame),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            rows("Filter Attack BETWEEN 65 AND 130 EXCLUSIVE", &frame),
+            vec![2]
+        );
+        assert_eq!(
+            rows(
+                "Filter Attack BETWEEN 55 AND 130 EXCLUSIVE OR Type = [Normal]",
+                &frame
+            ),
+            vec![1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn text_filters() {
+        let bytes = "Name,Attack\nFlareon,130\nVaporeon,65\nJolteon,65\nEevee,55\nMr. Mime,45\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(rows("Filter Name CONTAINS eon", &frame), vec![0, 1, 2]);
+        assert_eq!(rows("Filter Name STARTSWITH Ee", &frame), vec![3]);
+        assert_eq!(rows("Filter Name ENDSWITH vee", &frame), vec![3]);
+        assert_eq!(
+            rows("Filter Name MATCHES /^(Fl|Jo)/ OR Attack < 50", &frame),
+            vec![0, 2, 4]
+        );
+        assert_eq!(rows("Filter Name ILIKE %EON", &frame), vec![0, 1, 2]);
+        assert_eq!(rows("Filter Name ILIKE mr._mime", &frame), vec![4]);
+        assert!(exec("Filter Name MATCHES /(/", &frame).is_err());
+        assert!(exec("Filter Attack CONTAINS 5", &frame).is_err());
+    }
+
+    #[test]
+    fn null_filters() {
+        let bytes = "Name,Type 2,Attack,Found\nFlareon,,130,2020-01-01\nCharizard,Flying,,\nJolteon,NA,65,2020-03-01\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(rows("Filter Type 2 IS NULL", &frame), vec![0, 2]);
+        assert_eq!(rows("Filter Type 2 IS NOT NULL", &frame), vec![1]);
+        assert_eq!(
+            rows("Filter Attack IS NULL OR Found IS NULL", &frame),
+            vec![1]
+        );
+        assert_eq!(
+            rows("Filter NOT (Type 2 IS NULL) AND Name IS NOT NULL", &frame),
+            vec![1]
+        );
+        assert_eq!(rows("Filter NOT (Attack > 100)", &frame), vec![2]);
+        assert_eq!(
+            rows("Filter NOT (Attack > 100 OR Type 2 = Flying)", &frame),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            rows("Filter NOT (Attack > 100 AND Type 2 = Flying)", &frame),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn sorted_filters() {
+        let bytes = "Name,Type,Attack\nFlareon,Fire,130\nVaporeon,Water,65\nMoltres,Fire,100\nJolteon,Electric,65\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let keys = match exec("Sort Attack, Name DESC", &frame).unwrap() {
+            Slice::SortSlice(keys) => keys,
+            _ => panic!("Expected a sort"),
+        };
+        frame.sort_by(keys).unwrap();
+
+        assert_eq!(rows("Filter Attack < 120", &frame), vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn aggregate_commands() {
+        let bytes = "Name,Type,HP,Weight\nFlareon,Fire,65,25.0\nVaporeon,Water,130,29.0\nMoltres,Fire,90,\nJolteon,Electric,65,24.0\nBig,Normal,2147483647,\nBigger,Normal,2147483647,\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let value = |input| match exec(input, &frame).unwrap() {
+            Slice::AggregateSlice(value) => value,
+            _ => panic!("Expected an aggregate"),
+        };
+        assert_eq!(value("Max HP WHERE Type = Fire"), Scalar::Int(90));
+        assert_eq!(value("Min HP"), Scalar::Int(65));
+        assert_eq!(
+            value("Sum HP WHERE Type = Normal"),
+            Scalar::Int(4_294_967_294)
+        );
+        assert_eq!(value("Average Weight"), Scalar::Float(26.0));
+        assert_eq!(value("Median HP WHERE HP < 1000"), Scalar::Float(77.5));
+        assert_eq!(value("Count Weight"), Scalar::Int(3));
+        assert_eq!(value("Count Name WHERE Type != Fire"), Scalar::Int(4));
+        assert_eq!(value("Max Weight WHERE Type = Grass"), Scalar::Null);
+        assert!(exec("Sum Name", &frame).is_err());
+        assert!(exec("Sum Defense", &frame).is_err());
+    }
+
+    #[test]
+    fn group_by() {
+        let bytes = "Name,Type,Attack,HP\nFlareon,Fire,130,65\nVaporeon,Water,65,130\nMoltres,Fire,100,90\nJolteon,Electric,65,65\nCharmander,Fire,52,39\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let command = "GroupBy Type Aggregate avg(Attack), count(*), max(HP)";
+        let mut grouped = match exec(command, &frame).unwrap() {
+            Slice::GroupSlice(grouped) => grouped,
+            _ => panic!("Expected a group"),
+        };
+        let columns: Vec<Vec<String>> = grouped
+            .slice_columns(0, 10)
+            .iter()
+            .map(|column| column.text())
+            .collect();
+        assert_eq!(columns[0], ["Fire", "Water", "Electric"]);
+        assert_eq!(columns[1], ["94", "65", "65"]);
+        assert_eq!(columns[2], ["3", "1", "1"]);
+        assert_eq!(columns[3], ["90", "130", "65"]);
+        assert_eq!(grouped.find_by_name("count(*)").dtype(), Codes::Int32);
+
+        assert_eq!(rows("Filter count(*) > 1", &grouped), vec![0]);
+        grouped
+            .sort_by(vec![SortKey::new("max(HP)", true, false)])
+            .unwrap();
+        assert_eq!(grouped.index(), [1, 0, 2]);
+
+        assert!(exec("GroupBy Type Aggregate sum(Name)", &frame).is_err());
+        assert!(exec("GroupBy Defense Aggregate count(*)", &frame).is_err());
+    }
+
+    #[test]
+    fn add_column() {
+        let bytes =
+            "Name,HP,Attack,Weight\nFlareon,65,130,25.0\nVaporeon,130,65,\nJolteon,65,65,24.5\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+
+        let add = |frame: &mut Frame, input| match exec(input, frame).unwrap() {
+            Slice::AddColumnSlice(name, expr) => frame.add_column(&name, expr),
+            _ => panic!("Expected a column"),
+        };
+        add(&mut frame, "AddColumn Total = HP + Attack * 2").unwrap();
+        add(
+            &mut frame,
+            "AddColumn Ratio = -(Weight - 0.5) / (Total % 2)",
+        )
+        .unwrap();
+
+        let total = frame.find_by_name("Total");
+        assert_eq!(total.dtype(), Codes::Int32);
+        assert_eq!(total.text(0, 10), ["325", "260"]);
+        let ratio = frame.find_by_name("Ratio");
+        assert_eq!(ratio.dtype(), Codes::Float64);
+        assert_eq!(ratio.text(0, 10), ["-24.5", ""]);
+
+        frame.append_remainder().unwrap();
+        assert_eq!(frame.find_by_name("Total").text(2, 1), ["195"]);
+        assert_eq!(rows("Filter Total > 200", &frame), vec![0, 1]);
+
+        // Later rows are computed on their own, the column widens when they need it
+        frame
+            .append("Mew,2000000000,2000000000,4.0\n".as_bytes(), None)
+            .unwrap();
+        frame.append_remainder().unwrap();
+        let total = frame.find_by_name("Total");
+        assert_eq!(total.dtype(), Codes::Int64);
+        assert_eq!(total.text(0, 10), ["325", "260", "195", "6000000000"]);
+        assert_eq!(frame.find_by_name("Ratio").text(3, 1), ["-inf"]);
+
+        assert_eq!(
+            add(&mut frame, "AddColumn Total = HP"),
+            Err(SchemaError::DuplicateColumn("Total".into()))
+        );
+        assert_eq!(
+            add(&mut frame, "AddColumn Bad = Name * 2"),
+            Err(SchemaError::NotNumeric("Name".into()))
+        );
     }
 }
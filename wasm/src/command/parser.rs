@@ -1,48 +1,383 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_until},
-    character::complete::multispace0,
-    sequence::delimited,
+    bytes::complete::{is_not, tag, take_until},
+    character::complete::{char, multispace0, multispace1, one_of},
+    combinator::{all_consuming, eof, map, map_opt, not, opt, peek},
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated},
     IResult, Parser,
 };
 
+use crate::{
+    aggregate::Aggregate,
+    compute::{Expr, Operator},
+    group::GroupAggregate,
+    sort::SortKey,
+};
+
+/// The keyword starting a command, a whole word so that `Maximum` is not read as `Max`.
 pub fn parse_instruction(input: &str) -> IResult<&str, &str> {
-    alt((tag("Filter"), tag("Average")))(input)
+    let keyword = alt((
+        tag("Filter"),
+        tag("Sort"),
+        tag("GroupBy"),
+        tag("AddColumn"),
+        tag("Average"),
+        tag("Min"),
+        tag("Max"),
+        tag("Count"),
+        tag("Sum"),
+        tag("Median"),
+    ));
+    terminated(keyword, peek(alt((multispace1, eof))))(input)
 }
 
-pub fn parse_filter_column(symbol: &str) -> impl Parser<&str, &str, nom::error::Error<&str>> {
-    let column = take_until(symbol);
-    delimited(multispace0, column, multispace0)
-}
+const SYMBOLS: &[&str] = &[
+    " =",
+    " <",
+    " >",
+    " !",
+    " BETWEEN ",
+    " CONTAINS ",
+    " STARTSWITH ",
+    " ENDSWITH ",
+    " MATCHES ",
+    " ILIKE ",
+    " IS ",
+];
 
+/// Splits the column from the first symbol found in the condition.
 pub fn parse_filter_symbol(input: &str) -> IResult<&str, &str> {
+    let end = SYMBOLS
+        .iter()
+        .filter_map(|symbol| input.find(symbol))
+        .min()
+        .ok_or_else(|| {
+            nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::TakeUntil,
+            ))
+        })?;
+    let (column, tail) = input.split_at(end);
+    Ok((tail.trim_start(), column.trim()))
+}
+
+/// `Type 1, Attack DESC NULLS FIRST`
+pub fn parse_sort(input: &str) -> IResult<&str, Vec<SortKey>> {
+    let key = map_opt(is_not(","), SortKey::parse);
+    all_consuming(separated_list1(char(','), key))(input)
+}
+
+/// `Type 1, Type 2 Aggregate avg(Attack), count(*)`
+pub fn parse_group(input: &str) -> IResult<&str, (Vec<&str>, Vec<GroupAggregate>)> {
+    let (input, keys) = take_until(" Aggregate ")(input)?;
+    let (aggregates, _) = tag(" Aggregate ")(input)?;
+    let key = map_opt(is_not(","), |key: &str| {
+        let key = key.trim();
+        (!key.is_empty()).then_some(key)
+    });
+    let (_, keys) = all_consuming(separated_list1(char(','), key))(keys)?;
+    let aggregate = map_opt(is_not(","), GroupAggregate::parse);
+    let (_, aggregates) = all_consuming(separated_list1(char(','), aggregate))(aggregates)?;
+    Ok(("", (keys, aggregates)))
+}
+
+fn parse_factor(input: &str) -> IResult<&str, Expr> {
+    let input = input.trim_start();
     alt((
-        parse_filter_column(" ="),
-        parse_filter_column(" <"),
-        parse_filter_column(" >"),
+        map(preceded(char('-'), parse_factor), |expr| {
+            Expr::Neg(Box::new(expr))
+        }),
+        delimited(char('('), parse_sum, preceded(multispace0, char(')'))),
+        map_opt(is_not("+-*/%()"), Expr::operand),
     ))(input)
 }
 
-pub fn parse_filter(input: &str) -> IResult<&str, ParsedCommand> {
-    let err = Err(nom::Err::Error(nom::error::Error::new(
-        "Unknown command",
-        nom::error::ErrorKind::Tag,
-    )));
+/// Operands joined by operators of the same precedence, from left to right.
+fn binary<'a>(
+    operand: fn(&'a str) -> IResult<&'a str, Expr>,
+    operators: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Expr> {
+    move |input| {
+        let (input, first) = operand(input)?;
+        let operator = map_opt(
+            preceded(multispace0, one_of(operators)),
+            Operator::from_char,
+        );
+        let (input, rest) = many0(pair(operator, operand))(input)?;
+        let expr = rest.into_iter().fold(first, |left, (op, right)| {
+            Expr::Binary(op, Box::new(left), Box::new(right))
+        });
+        Ok((input, expr))
+    }
+}
+
+fn parse_product(input: &str) -> IResult<&str, Expr> {
+    binary(parse_factor, "*/%")(input)
+}
+
+fn parse_sum(input: &str) -> IResult<&str, Expr> {
+    binary(parse_product, "+-")(input)
+}
+
+/// `Total = HP + Attack * 2`, with `+ - * / %`, unary minus and parentheses.
+pub fn parse_add_column(input: &str) -> IResult<&str, (&str, Expr)> {
+    let (input, name) = take_until("=")(input)?;
+    let (input, _) = char('=')(input)?;
+    let (_, expr) = all_consuming(terminated(parse_sum, multispace0))(input)?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Eof,
+        )));
+    }
+    Ok(("", (name, expr)))
+}
+
+/// `HP WHERE Type 1 = Fire`, the column followed by an optional filter.
+fn parse_aggregate(input: &str) -> IResult<&str, (&str, Option<ParsedCommand<'_>>)> {
+    let (column, condition) = match input.split_once(" WHERE") {
+        Some((column, condition)) => {
+            let (_, condition) =
+                all_consuming(delimited(multispace1, parse_expression, multispace0))(condition)?;
+            (column, Some(condition))
+        }
+        None => (input, None),
+    };
+    let column = column.trim();
+    if column.is_empty() {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Eof,
+        )));
+    }
+    Ok(("", (column, condition)))
+}
+
+/// `[Fire, Water, Grass]`
+fn parse_list(input: &str) -> IResult<&str, Vec<&str>> {
+    let item = map(is_not(",]"), str::trim);
+    delimited(char('['), separated_list1(char(','), item), char(']'))(input)
+}
+
+/// `50 AND 100`, followed by `EXCLUSIVE` to leave both bounds out.
+fn parse_bounds(input: &str) -> IResult<&str, (&str, &str, bool)> {
+    let (input, low) = alt((quoted, map(take_until(" AND "), str::trim)))(input)?;
+    let (input, _) = keyword("AND").parse(input)?;
+    let (input, high) = parse_value(input)?;
+    let (input, exclusive) = opt(preceded(multispace1, tag("EXCLUSIVE")))(input)?;
+    Ok(match high.strip_suffix(" EXCLUSIVE") {
+        Some(high) if exclusive.is_none() => (input, (low, high.trim_end(), false)),
+        _ => (input, (low, high, exclusive.is_none())),
+    })
+}
+
+/// `"Fire AND Water"`, the text between double quotes as is.
+fn quoted(input: &str) -> IResult<&str, &str> {
+    delimited(char('"'), take_until("\""), char('"'))(input)
+}
+
+/// Whether another condition follows, after `AND` or `OR`.
+fn continues(input: &str) -> bool {
+    preceded(alt((keyword("AND"), keyword("OR"))), parse_atom)(input).is_ok()
+}
+
+/// Whether the condition is over, at the end of the input, a closing parenthesis or
+/// another condition.
+fn ends(input: &str) -> bool {
+    let rest = input.trim_start();
+    rest.is_empty() || rest.starts_with(')') || continues(input)
+}
+
+/// Text up to the end of the condition. An `AND` or `OR` is part of the value unless
+/// a condition follows it, so `Name = SAND OR STORM` compares with the whole text.
+fn bare(input: &str) -> IResult<&str, &str> {
+    let mut depth = 0usize;
+    let end = input.char_indices().find_map(|(i, c)| match c {
+        '(' => {
+            depth += 1;
+            None
+        }
+        ')' if depth == 0 => Some(i),
+        ')' => {
+            depth -= 1;
+            None
+        }
+        ' ' if continues(&input[i..]) => Some(i),
+        _ => None,
+    });
+    let (value, rest) = input.split_at(end.unwrap_or(input.len()));
+    Ok((rest, value.trim_end()))
+}
+
+/// A value opening a quote has to close it.
+fn parse_value(input: &str) -> IResult<&str, &str> {
+    alt((quoted, preceded(not(char('"')), bare)))(input)
+}
+
+/// `/^Fl/`, the pattern ends at the first slash closing the condition.
+fn parse_pattern(input: &str) -> IResult<&str, &str> {
+    let (body, _) = char('/')(input)?;
+    body.match_indices('/')
+        .map(|(i, _)| i)
+        .find(|&i| ends(&body[i + 1..]))
+        .map(|i| (&body[i + 1..], &body[..i]))
+        .ok_or_else(|| {
+            nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::TakeUntil,
+            ))
+        })
+}
+
+/// A single condition, `Type 1 = Fire`, the value can be quoted to hold `AND`, `OR`
+/// or parentheses.
+pub fn parse_filter<'a>(input: &'a str) -> IResult<&'a str, ParsedCommand<'a>> {
+    let err = |input| {
+        Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        )))
+    };
 
     let (input, column) = parse_filter_symbol(input)?;
-    let (value, symbol) = alt((tag("= "), tag("< "), tag("> ")))(input)?;
+    let balanced = column.matches('(').count() == column.matches(')').count();
+    if column.is_empty() || !balanced || unmatched_paren(column).is_some() {
+        return err(input);
+    }
+    let (value, symbol) = alt((
+        tag("= "),
+        tag("<= "),
+        tag(">= "),
+        tag("!= "),
+        tag("< "),
+        tag("> "),
+        tag("BETWEEN "),
+        tag("CONTAINS "),
+        tag("STARTSWITH "),
+        tag("ENDSWITH "),
+        tag("MATCHES "),
+        tag("ILIKE "),
+        tag("IS NOT NULL"),
+        tag("IS NULL"),
+    ))(input)?;
+    let condition = |command: fn(&'a str, &'a str) -> ParsedCommand<'a>| {
+        map(parse_value, move |value| command(column, value))
+    };
     match symbol {
-        "= " => Ok(("", ParsedCommand::EqualFilter(column, value))),
-        _ => err,
+        "= " => alt((
+            map(terminated(parse_list, multispace0), |values| {
+                ParsedCommand::InFilter(column, values)
+            }),
+            condition(ParsedCommand::EqualFilter),
+        ))(value),
+        "BETWEEN " => map(parse_bounds, |(low, high, inclusive)| {
+            ParsedCommand::BetweenFilter(column, low, high, inclusive)
+        })(value),
+        "CONTAINS " => condition(ParsedCommand::ContainsFilter)(value),
+        "STARTSWITH " => condition(ParsedCommand::StartsWithFilter)(value),
+        "ENDSWITH " => condition(ParsedCommand::EndsWithFilter)(value),
+        "MATCHES " => map(parse_pattern, |pattern| {
+            ParsedCommand::MatchesFilter(column, pattern)
+        })(value),
+        "ILIKE " => condition(ParsedCommand::ILikeFilter)(value),
+        "IS NULL" if ends(value) => Ok((value, ParsedCommand::IsNullFilter(column))),
+        "IS NOT NULL" if ends(value) => Ok((value, ParsedCommand::IsNotNullFilter(column))),
+        "< " => condition(ParsedCommand::LessFilter)(value),
+        "<= " => condition(ParsedCommand::LessEqualFilter)(value),
+        "> " => condition(ParsedCommand::GreaterFilter)(value),
+        ">= " => condition(ParsedCommand::GreaterEqualFilter)(value),
+        "!= " => condition(ParsedCommand::NotEqualFilter)(value),
+        _ => err(value),
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Position of the first closing parenthesis without an opening one before it,
+/// so column names like `count(*)` stay whole.
+fn unmatched_paren(input: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    input.char_indices().find_map(|(i, c)| match c {
+        '(' => {
+            depth += 1;
+            None
+        }
+        ')' if depth == 0 => Some(i),
+        ')' => {
+            depth -= 1;
+            None
+        }
+        _ => None,
+    })
+}
+
+fn keyword<'a>(word: &'static str) -> impl Parser<&'a str, &'a str, nom::error::Error<&'a str>> {
+    delimited(multispace0, tag(word), multispace1)
+}
+
+fn parse_atom(input: &str) -> IResult<&str, ParsedCommand<'_>> {
+    let input = input.trim_start();
+    alt((
+        map(preceded(keyword("NOT"), parse_atom), |command| {
+            ParsedCommand::Not(Box::new(command))
+        }),
+        delimited(
+            char('('),
+            parse_expression,
+            preceded(multispace0, char(')')),
+        ),
+        parse_filter,
+    ))(input)
+}
+
+fn parse_and(input: &str) -> IResult<&str, ParsedCommand<'_>> {
+    let (input, first) = parse_atom(input)?;
+    let (input, rest) = many0(preceded(keyword("AND"), parse_atom))(input)?;
+    let command = rest.into_iter().fold(first, |left, right| {
+        ParsedCommand::And(Box::new(left), Box::new(right))
+    });
+    Ok((input, command))
+}
+
+/// Conditions combined with `AND`, `OR`, `NOT` and parentheses, `AND` binds tighter than `OR`.
+pub fn parse_expression(input: &str) -> IResult<&str, ParsedCommand<'_>> {
+    let (input, first) = parse_and(input)?;
+    let (input, rest) = many0(preceded(keyword("OR"), parse_and))(input)?;
+    let command = rest.into_iter().fold(first, |left, right| {
+        ParsedCommand::Or(Box::new(left), Box::new(right))
+    });
+    Ok((input, command))
+}
+
+#[derive(Debug, PartialEq)]
 pub enum ParsedCommand<'a> {
     EqualFilter(&'a str, &'a str),
     LessFilter(&'a str, &'a str),
     GreaterFilter(&'a str, &'a str),
-    Average,
+    LessEqualFilter(&'a str, &'a str),
+    GreaterEqualFilter(&'a str, &'a str),
+    NotEqualFilter(&'a str, &'a str),
+    InFilter(&'a str, Vec<&'a str>),
+    /// Column, low and high bounds, whether the bounds are part of the range.
+    BetweenFilter(&'a str, &'a str, &'a str, bool),
+    ContainsFilter(&'a str, &'a str),
+    StartsWithFilter(&'a str, &'a str),
+    EndsWithFilter(&'a str, &'a str),
+    /// Pattern written between slashes, `/^Fl/`.
+    MatchesFilter(&'a str, &'a str),
+    ILikeFilter(&'a str, &'a str),
+    IsNullFilter(&'a str),
+    IsNotNullFilter(&'a str),
+    And(Box<ParsedCommand<'a>>, Box<ParsedCommand<'a>>),
+    Or(Box<ParsedCommand<'a>>, Box<ParsedCommand<'a>>),
+    Not(Box<ParsedCommand<'a>>),
+    Sort(Vec<SortKey>),
+    /// Key columns and the aggregates computed for each group.
+    GroupBy(Vec<&'a str>, Vec<GroupAggregate>),
+    /// Name of the new column and the expression computing it.
+    AddColumn(&'a str, Expr),
+    /// Aggregate of a column over the rows kept by the optional filter.
+    Aggregate(Aggregate, &'a str, Option<Box<ParsedCommand<'a>>>),
 }
 
 pub fn parse_command(input: &str) -> IResult<&str, ParsedCommand> {
@@ -54,28 +389,277 @@ pub fn parse_command(input: &str) -> IResult<&str, ParsedCommand> {
 
     match keyword {
         "Filter" => {
-            let (_, command) = parse_filter(tail)?;
+            let (_, command) = all_consuming(terminated(parse_expression, multispace0))(tail)?;
             Ok((keyword, command))
         }
-        _ => err,
+        "Sort" => {
+            let (_, keys) = parse_sort(tail)?;
+            Ok((keyword, ParsedCommand::Sort(keys)))
+        }
+        "AddColumn" => {
+            let (_, (name, expr)) = parse_add_column(tail)?;
+            Ok((keyword, ParsedCommand::AddColumn(name, expr)))
+        }
+        "GroupBy" => {
+            let (_, (keys, aggregates)) = parse_group(tail)?;
+            Ok((keyword, ParsedCommand::GroupBy(keys, aggregates)))
+        }
+        keyword => match Aggregate::parse(keyword) {
+            Some(aggregate) => {
+                let (_, (column, condition)) = parse_aggregate(tail)?;
+                let command = ParsedCommand::Aggregate(aggregate, column, condition.map(Box::new));
+                Ok((keyword, command))
+            }
+            None => err,
+        },
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::parse_instruction;
-    use crate::command::parser::{parse_filter, ParsedCommand};
+    use crate::{
+        aggregate::{Aggregate, Scalar},
+        command::parser::{parse_command, parse_filter, ParsedCommand},
+        compute::{Expr, Operator},
+        group::GroupAggregate,
+        sort::SortKey,
+    };
 
     #[test]
     fn command() {
         let (tail, command) = parse_instruction("Filter Type 1 = Fire").unwrap();
         let ret = match command {
             "Filter" => parse_filter(tail).unwrap().1,
-            _ => ParsedCommand::Average,
+            _ => unreachable!(),
         };
         assert_eq!(ret, ParsedCommand::EqualFilter("Type 1", "Fire"));
     }
 
+    #[test]
+    fn comparisons() {
+        let parse = |input| parse_filter(input).unwrap().1;
+        assert_eq!(
+            parse(" Attack < 50"),
+            ParsedCommand::LessFilter("Attack", "50")
+        );
+        assert_eq!(
+            parse(" Attack <= 50"),
+            ParsedCommand::LessEqualFilter("Attack", "50")
+        );
+        assert_eq!(
+            parse(" Sp. Atk > 50"),
+            ParsedCommand::GreaterFilter("Sp. Atk", "50")
+        );
+        assert_eq!(
+            parse(" Attack >= 50"),
+            ParsedCommand::GreaterEqualFilter("Attack", "50")
+        );
+        assert_eq!(
+            parse(" Type 1 != Fire"),
+            ParsedCommand::NotEqualFilter("Type 1", "Fire")
+        );
+    }
+
+    #[test]
+    fn expression() {
+        use ParsedCommand::{And, EqualFilter, Not, Or};
+
+        let (_, command) =
+            parse_command("Filter (Type 1 = Fire OR Type 1 = Water) AND NOT Legendary = true")
+                .unwrap();
+        let expected = And(
+            Box::new(Or(
+                Box::new(EqualFilter("Type 1", "Fire")),
+                Box::new(EqualFilter("Type 1", "Water")),
+            )),
+            Box::new(Not(Box::new(EqualFilter("Legendary", "true")))),
+        );
+        assert_eq!(command, expected);
+
+        let (_, command) = parse_command("Filter A = 1 OR B = 2 AND C = 3").unwrap();
+        let expected = Or(
+            Box::new(EqualFilter("A", "1")),
+            Box::new(And(
+                Box::new(EqualFilter("B", "2")),
+                Box::new(EqualFilter("C", "3")),
+            )),
+        );
+        assert_eq!(command, expected);
+
+        assert!(parse_command("Filter (A = 1").is_err());
+        assert!(parse_command("Filter A = 1) OR B = 2").is_err());
+    }
+
+    #[test]
+    fn in_and_between() {
+        use ParsedCommand::{And, BetweenFilter, InFilter, Not};
+
+        let (_, command) = parse_command("Filter Type = [Fire, Water, Grass]").unwrap();
+        assert_eq!(command, InFilter("Type", vec!["Fire", "Water", "Grass"]));
+
+        let (_, command) =
+            parse_command("Filter Attack BETWEEN 50 AND 100 AND NOT Type = [Fire]").unwrap();
+        let expected = And(
+            Box::new(BetweenFilter("Attack", "50", "100", true)),
+            Box::new(Not(Box::new(InFilter("Type", vec!["Fire"])))),
+        );
+        assert_eq!(command, expected);
+
+        let (_, command) = parse_command("Filter Attack BETWEEN 50 AND 100 EXCLUSIVE").unwrap();
+        assert_eq!(command, BetweenFilter("Attack", "50", "100", false));
+        assert!(parse_command("Filter Attack BETWEEN 50").is_err());
+    }
+
+    #[test]
+    fn text_predicates() {
+        use ParsedCommand::{ContainsFilter, ILikeFilter, MatchesFilter, Or, StartsWithFilter};
+
+        let (_, command) = parse_command("Filter Name CONTAINS eon = x").unwrap();
+        assert_eq!(command, ContainsFilter("Name", "eon = x"));
+
+        let (_, command) =
+            parse_command("Filter Name MATCHES /^(Fl|Jo)[a-z]+/ OR Name STARTSWITH Va").unwrap();
+        let expected = Or(
+            Box::new(MatchesFilter("Name", "^(Fl|Jo)[a-z]+")),
+            Box::new(StartsWithFilter("Name", "Va")),
+        );
+        assert_eq!(command, expected);
+
+        let (_, command) = parse_command("Filter Name ILIKE %EON").unwrap();
+        assert_eq!(command, ILikeFilter("Name", "%EON"));
+        assert!(parse_command("Filter Name MATCHES eon").is_err());
+    }
+
+    #[test]
+    fn values() {
+        use ParsedCommand::{BetweenFilter, ContainsFilter, EqualFilter, Or};
+
+        let (_, command) = parse_command("Filter Name = SAND OR STORM").unwrap();
+        assert_eq!(command, EqualFilter("Name", "SAND OR STORM"));
+
+        let (_, command) = parse_command("Filter Name = SAND OR Name = STORM").unwrap();
+        let expected = Or(
+            Box::new(EqualFilter("Name", "SAND")),
+            Box::new(EqualFilter("Name", "STORM")),
+        );
+        assert_eq!(command, expected);
+
+        let (_, command) =
+            parse_command("Filter Name CONTAINS \"a AND Type = b\" OR Type = Fire").unwrap();
+        let expected = Or(
+            Box::new(ContainsFilter("Name", "a AND Type = b")),
+            Box::new(EqualFilter("Type", "Fire")),
+        );
+        assert_eq!(command, expected);
+
+        let (_, command) = parse_command("Filter (Name = \"Mr. (Mime)\")").unwrap();
+        assert_eq!(command, EqualFilter("Name", "Mr. (Mime)"));
+
+        let (_, command) = parse_command("Filter Name BETWEEN \"A AND B\" AND C").unwrap();
+        assert_eq!(command, BetweenFilter("Name", "A AND B", "C", true));
+        assert!(parse_command("Filter Name = \"SAND").is_err());
+        assert!(parse_command("Filter Name = \"SAND\" STORM").is_err());
+    }
+
+    #[test]
+    fn null_predicates() {
+        use ParsedCommand::{And, IsNotNullFilter, IsNullFilter};
+
+        let (_, command) = parse_command("Filter Type 2 IS NULL AND Attack IS NOT NULL").unwrap();
+        let expected = And(
+            Box::new(IsNullFilter("Type 2")),
+            Box::new(IsNotNullFilter("Attack")),
+        );
+        assert_eq!(command, expected);
+        assert!(parse_command("Filter Type 2 IS NULLABLE").is_err());
+    }
+
+    #[test]
+    fn sort() {
+        let (_, command) = parse_command("Sort Type 1, Attack DESC NULLS FIRST").unwrap();
+        let keys = vec![
+            SortKey::new("Type 1", false, false),
+            SortKey::new("Attack", true, true),
+        ];
+        assert_eq!(command, ParsedCommand::Sort(keys));
+        assert!(parse_command("Sort Type 1,").is_err());
+    }
+
+    #[test]
+    fn aggregate() {
+        let (_, command) = parse_command("Average Attack").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Aggregate(Aggregate::Average, "Attack", None)
+        );
+
+        let (_, command) = parse_command("Max HP WHERE Type 1 = Fire").unwrap();
+        let condition = ParsedCommand::EqualFilter("Type 1", "Fire");
+        assert_eq!(
+            command,
+            ParsedCommand::Aggregate(Aggregate::Max, "HP", Some(Box::new(condition)))
+        );
+        assert!(parse_command("Sum ").is_err());
+        assert!(parse_command("Count Name WHERE").is_err());
+    }
+
+    #[test]
+    fn group_by() {
+        let (_, command) =
+            parse_command("GroupBy Type 1, Type 2 Aggregate avg(Attack), count(*), max(HP)")
+                .unwrap();
+        let aggregates = vec![
+            GroupAggregate::new(Aggregate::Average, "Attack"),
+            GroupAggregate::new(Aggregate::Count, "*"),
+            GroupAggregate::new(Aggregate::Max, "HP"),
+        ];
+        assert_eq!(
+            command,
+            ParsedCommand::GroupBy(vec!["Type 1", "Type 2"], aggregates)
+        );
+        assert!(parse_command("GroupBy Type 1").is_err());
+        assert!(parse_command("GroupBy Type 1 Aggregate avg(*)").is_err());
+        assert!(parse_command("GroupBy , Aggregate count(*)").is_err());
+
+        let (_, command) = parse_command("Filter (count(*) > 1 AND Type = Fire)").unwrap();
+        let left = ParsedCommand::GreaterFilter("count(*)", "1");
+        let right = ParsedCommand::EqualFilter("Type", "Fire");
+        assert_eq!(command, ParsedCommand::And(Box::new(left), Box::new(right)));
+    }
+
+    #[test]
+    fn add_column() {
+        let (_, command) = parse_command("AddColumn Total = HP + Attack * 2").unwrap();
+        let product = Expr::Binary(
+            Operator::Mul,
+            Box::new(Expr::Column("Attack".into())),
+            Box::new(Expr::Number(Scalar::Int(2))),
+        );
+        let sum = Expr::Binary(
+            Operator::Add,
+            Box::new(Expr::Column("HP".into())),
+            Box::new(product),
+        );
+        assert_eq!(command, ParsedCommand::AddColumn("Total", sum));
+
+        let (_, command) = parse_command("AddColumn Ratio = -(Sp. Atk - 1) / 2").unwrap();
+        let difference = Expr::Binary(
+            Operator::Sub,
+            Box::new(Expr::Column("Sp. Atk".into())),
+            Box::new(Expr::Number(Scalar::Int(1))),
+        );
+        let ratio = Expr::Binary(
+            Operator::Div,
+            Box::new(Expr::Neg(Box::new(difference))),
+            Box::new(Expr::Number(Scalar::Int(2))),
+        );
+        assert_eq!(command, ParsedCommand::AddColumn("Ratio", ratio));
+        assert!(parse_command("AddColumn Total = HP +").is_err());
+        assert!(parse_command("AddColumn = HP").is_err());
+        assert!(parse_command("AddColumn Total = (HP").is_err());
+    }
+
     #[test]
     fn err() {
         let res = parse_instruction("NoCommand Type 1 = Fire");
@@ -84,5 +668,10 @@ mod test {
             nom::error::ErrorKind::Tag,
         )));
         assert_eq!(res, err);
+
+        assert!(parse_instruction("Maximum HP").is_err());
+        assert!(parse_instruction("Counter").is_err());
+        assert!(parse_command("Sorted Name").is_err());
+        assert_eq!(parse_instruction("Count"), Ok(("", "Count")));
     }
 }
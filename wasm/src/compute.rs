@@ -0,0 +1,205 @@
+use core::ops::Range;
+
+use crate::{
+    aggregate::{scalar_column, Scalar},
+    column::Column,
+    diagnostics::SchemaError,
+    series::SeriesTrait,
+    slice::widen_f32,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Operator {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Self::Add),
+            '-' => Some(Self::Sub),
+            '*' => Some(Self::Mul),
+            '/' => Some(Self::Div),
+            '%' => Some(Self::Rem),
+            _ => None,
+        }
+    }
+
+    /// Integers stay exact until they overflow `i128`, divisions always give floats and
+    /// an integer remainder by zero gives a null.
+    fn apply(self, left: Scalar, right: Scalar) -> Scalar {
+        match (left, right) {
+            (Scalar::Null, _) | (_, Scalar::Null) => Scalar::Null,
+            (Scalar::Int(a), Scalar::Int(b)) if self != Operator::Div => {
+                let exact = match self {
+                    Operator::Add => a.checked_add(b),
+                    Operator::Sub => a.checked_sub(b),
+                    Operator::Mul => a.checked_mul(b),
+                    _ => return a.checked_rem(b).map_or(Scalar::Null, Scalar::Int),
+                };
+                exact.map_or_else(|| self.apply_float(a as f64, b as f64), Scalar::Int)
+            }
+            (a, b) => self.apply_float(
+                a.to_f64().unwrap_or_default(),
+                b.to_f64().unwrap_or_default(),
+            ),
+        }
+    }
+
+    fn apply_float(self, a: f64, b: f64) -> Scalar {
+        Scalar::Float(match self {
+            Operator::Add => a + b,
+            Operator::Sub => a - b,
+            Operator::Mul => a * b,
+            Operator::Div => a / b,
+            Operator::Rem => a % b,
+        })
+    }
+}
+
+/// Arithmetic over the columns of a frame, evaluated row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(Scalar),
+    Column(String),
+    Neg(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// A literal when the text starts like a number, else a column name.
+    pub fn operand(text: &str) -> Option<Self> {
+        let text = text.trim();
+        match text.chars().next()? {
+            '0'..='9' | '.' => {
+                let number = match text.parse() {
+                    Ok(int) => Scalar::Int(int),
+                    Err(_) => Scalar::Float(text.parse().ok()?),
+                };
+                Some(Expr::Number(number))
+            }
+            _ => Some(Expr::Column(text.into())),
+        }
+    }
+
+    /// Values of the rows, along with whether the result is a float whatever the values.
+    fn evaluate(
+        &self,
+        columns: &[Column],
+        rows: Range<usize>,
+    ) -> Result<(Vec<Scalar>, bool), SchemaError> {
+        match self {
+            Expr::Number(number) => {
+                let float = matches!(number, Scalar::Float(_));
+                Ok((vec![*number; rows.len()], float))
+            }
+            Expr::Column(name) => {
+                let column = columns
+                    .iter()
+                    .find(|column| column.name() == name)
+                    .ok_or_else(|| SchemaError::UnknownColumn(name.clone()))?;
+                scalars(column.series(), rows).ok_or_else(|| SchemaError::NotNumeric(name.clone()))
+            }
+            Expr::Neg(inner) => {
+                let (values, float) = inner.evaluate(columns, rows)?;
+                let zero = Scalar::Int(0);
+                let values = values
+                    .into_iter()
+                    .map(|value| match value {
+                        Scalar::Float(value) => Scalar::Float(-value),
+                        value => Operator::Sub.apply(zero, value),
+                    })
+                    .collect();
+                Ok((values, float))
+            }
+            Expr::Binary(op, left, right) => {
+                let (left, left_float) = left.evaluate(columns, rows.clone())?;
+                let (right, right_float) = right.evaluate(columns, rows)?;
+                let values = left
+                    .into_iter()
+                    .zip(right)
+                    .map(|(left, right)| op.apply(left, right))
+                    .collect();
+                Ok((values, left_float || right_float || *op == Operator::Div))
+            }
+        }
+    }
+}
+
+fn scalars(series: &dyn SeriesTrait, rows: Range<usize>) -> Option<(Vec<Scalar>, bool)> {
+    fn collect<T: Copy>(
+        values: &[Option<T>],
+        rows: Range<usize>,
+        wrap: impl Fn(T) -> Scalar,
+    ) -> Vec<Scalar> {
+        let values = values[rows]
+            .iter()
+            .map(|value| value.map_or(Scalar::Null, &wrap));
+        values.collect()
+    }
+
+    if let Ok(values) = series.i32() {
+        Some((collect(values, rows, |x| Scalar::Int(x.into())), false))
+    } else if let Ok(values) = series.i64() {
+        Some((collect(values, rows, |x| Scalar::Int(x.into())), false))
+    } else if let Ok(values) = series.i128() {
+        Some((collect(values, rows, Scalar::Int), false))
+    } else if let Ok(values) = series.f32() {
+        Some((collect(values, rows, |x| Scalar::Float(widen_f32(x))), true))
+    } else if let Ok(values) = series.f64() {
+        Some((collect(values, rows, Scalar::Float), true))
+    } else {
+        None
+    }
+}
+
+/// Evaluates the expression over the rows into a new column, integer results get the
+/// narrowest integer type.
+pub fn compute_column(
+    name: &str,
+    expr: &Expr,
+    columns: &[Column],
+    rows: Range<usize>,
+) -> Result<Column, SchemaError> {
+    let (mut values, float) = expr.evaluate(columns, rows)?;
+    if float {
+        values
+            .iter_mut()
+            .filter_map(|value| value.to_f64().map(|float| (value, float)))
+            .for_each(|(value, float)| *value = Scalar::Float(float));
+    }
+    Ok(scalar_column(name.into(), values))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Expr, Operator};
+    use crate::aggregate::Scalar;
+
+    #[test]
+    fn apply() {
+        let (int, float) = (Scalar::Int(7), Scalar::Float(2.0));
+        assert_eq!(Operator::Add.apply(int, Scalar::Int(1)), Scalar::Int(8));
+        assert_eq!(Operator::Div.apply(int, Scalar::Int(2)), Scalar::Float(3.5));
+        assert_eq!(Operator::Rem.apply(int, Scalar::Int(0)), Scalar::Null);
+        assert_eq!(Operator::Mul.apply(int, float), Scalar::Float(14.0));
+        assert_eq!(Operator::Sub.apply(Scalar::Null, float), Scalar::Null);
+        assert_eq!(
+            Operator::Mul.apply(Scalar::Int(i128::MAX), Scalar::Int(2)),
+            Scalar::Float(i128::MAX as f64 * 2.0)
+        );
+
+        assert_eq!(Expr::operand(" 2 "), Some(Expr::Number(Scalar::Int(2))));
+        assert_eq!(Expr::operand("0.5"), Some(Expr::Number(Scalar::Float(0.5))));
+        assert_eq!(
+            Expr::operand("Sp. Atk "),
+            Some(Expr::Column("Sp. Atk".into()))
+        );
+        assert_eq!(Expr::operand("2x"), None);
+        assert_eq!(Expr::operand("  "), None);
+    }
+}
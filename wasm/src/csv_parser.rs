@@ -1,23 +1,180 @@
-use std::str;
+use std::{borrow::Cow, str};
+
+use wasm_bindgen::prelude::wasm_bindgen;
 
 pub fn to_str(bytes: Option<&[u8]>) -> Option<&str> {
     bytes.map(|b| str::from_utf8(b).unwrap())
 }
 
-#[inline]
-fn slice_bytes(bytes: &[u8], border: i32, offset: i32) -> Option<&[u8]> {
-    Some(&bytes[border as usize..(offset - border - 1) as usize])
+/// A parsed field, borrowed from the input unless unescaping had to rewrite it.
+pub type Cell<'a> = Cow<'a, [u8]>;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineTerminator {
+    Any,
+    Lf,
+    CrLf,
+    Cr,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub(crate) delimiter: u8,
+    pub(crate) quote: u8,
+    pub(crate) escape: Option<u8>,
+    pub(crate) terminator: LineTerminator,
+}
+
+impl CsvDialect {
+    pub fn new(delimiter: u8, quote: u8, escape: Option<u8>, terminator: LineTerminator) -> Self {
+        Self {
+            delimiter,
+            quote,
+            escape,
+            terminator,
+        }
+    }
+
+    /// An escape byte equal to the quote byte is the RFC 4180 doubled quote,
+    /// which the quoting state machine already handles.
+    #[inline]
+    fn is_escape(&self, byte: u8) -> bool {
+        self.escape
+            .is_some_and(|escape| escape == byte && escape != self.quote)
+    }
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self::new(b',', b'"', None, LineTerminator::Any)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Quoting {
+    FieldStart,
+    Unquoted,
+    Quoted,
+    QuoteInQuoted,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token {
+    Data,
+    Skip,
+    Delimiter,
+}
+
+/// RFC 4180 state machine: a quote only opens a quoted section at the start of
+/// a field, a doubled quote inside it is a literal quote and anything after the
+/// closing quote is kept as plain data.
+struct Scanner {
+    dialect: CsvDialect,
+    state: Quoting,
+    escaped: bool,
+}
+
+impl Scanner {
+    fn new(dialect: CsvDialect) -> Self {
+        Self {
+            dialect,
+            state: Quoting::FieldStart,
+            escaped: false,
+        }
+    }
+
+    #[inline]
+    fn in_quotes(&self) -> bool {
+        self.escaped || self.state == Quoting::Quoted
+    }
+
+    #[inline]
+    fn advance(&mut self, byte: u8) -> Token {
+        if self.escaped {
+            self.escaped = false;
+            if self.state != Quoting::Quoted {
+                self.state = Quoting::Unquoted;
+            }
+            return Token::Data;
+        }
+
+        match self.state {
+            Quoting::Quoted if self.dialect.is_escape(byte) => {
+                self.escaped = true;
+                Token::Skip
+            }
+            Quoting::Quoted if byte == self.dialect.quote => {
+                self.state = Quoting::QuoteInQuoted;
+                Token::Skip
+            }
+            Quoting::Quoted => Token::Data,
+            Quoting::QuoteInQuoted if byte == self.dialect.quote => {
+                self.state = Quoting::Quoted;
+                Token::Data
+            }
+            Quoting::FieldStart if byte == self.dialect.quote => {
+                self.state = Quoting::Quoted;
+                Token::Skip
+            }
+            _ if self.dialect.is_escape(byte) => {
+                self.escaped = true;
+                Token::Skip
+            }
+            _ if byte == self.dialect.delimiter => {
+                self.state = Quoting::FieldStart;
+                Token::Delimiter
+            }
+            _ => {
+                self.state = Quoting::Unquoted;
+                Token::Data
+            }
+        }
+    }
 }
 
 pub struct LineSplitter<'a> {
     bytes: &'a [u8],
-    finish: bool,
+    origin: &'a [u8],
+    dialect: CsvDialect,
 }
 
 impl<'a> LineSplitter<'a> {
     pub fn from_bytes(bytes: &'a [u8]) -> Self {
-        let finish = false;
-        Self { bytes, finish }
+        Self::new(bytes, CsvDialect::default())
+    }
+
+    pub fn new(bytes: &'a [u8], dialect: CsvDialect) -> Self {
+        Self {
+            bytes,
+            origin: bytes,
+            dialect,
+        }
+    }
+
+    /// Byte offset of a line yielded by this splitter from the start of its input.
+    pub fn offset_of(&self, line: &[u8]) -> usize {
+        line.as_ptr() as usize - self.origin.as_ptr() as usize
+    }
+
+    /// Returns the length of the line terminator starting at `i`, if any.
+    /// A lone `\r` at the very end of the buffer counts as a terminator so that
+    /// a `\r\n` split across two chunks does not leak into the last field.
+    #[inline]
+    fn terminator_at(&self, i: usize) -> Option<usize> {
+        let next = self.bytes.get(i + 1).copied();
+        match (self.dialect.terminator, self.bytes[i]) {
+            (LineTerminator::Lf | LineTerminator::Any, b'\n') => Some(1),
+            (LineTerminator::Cr, b'\r') => Some(1),
+            (LineTerminator::CrLf | LineTerminator::Any, b'\r') => match next {
+                Some(b'\n') => Some(2),
+                None => Some(1),
+                _ if self.dialect.terminator == LineTerminator::Any => Some(1),
+                _ => None,
+            },
+            _ => None,
+        }
     }
 }
 
@@ -25,99 +182,115 @@ impl<'a> Iterator for LineSplitter<'a> {
     type Item = &'a [u8];
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finish {
-            return None;
-        }
-
-        let mut cursor = 0i32;
-        let mut iter = self.bytes.iter();
-        let mut quoted = false;
+        while !self.bytes.is_empty() {
+            let mut scanner = Scanner::new(self.dialect);
+            let mut split = None;
 
-        loop {
-            cursor += 1;
-            match iter.next() {
-                Some(&byte) if byte == b'"' => {
-                    quoted = !quoted;
-                }
-                Some(&byte) if byte == b'\n' && !quoted => {
-                    break;
-                }
-                None if !self.finish && cursor > 1 => {
-                    self.finish = !self.finish;
-                    return Some(self.bytes);
-                }
-                None => {
-                    return None;
-                }
-                _ => {
-                    continue;
+            for (i, &byte) in self.bytes.iter().enumerate() {
+                if !scanner.in_quotes() {
+                    if let Some(len) = self.terminator_at(i) {
+                        split = Some((i, i + len));
+                        break;
+                    }
                 }
+                scanner.advance(byte);
+            }
+
+            let (end, next) = split.unwrap_or((self.bytes.len(), self.bytes.len()));
+            let line = &self.bytes[..end];
+            self.bytes = &self.bytes[next..];
+
+            // Blank lines carry no fields, skipping them keeps the columns aligned
+            if !line.is_empty() {
+                return Some(line);
             }
         }
 
-        let res = Some(&self.bytes[..(cursor - 1) as usize]);
-        self.bytes = &self.bytes[(cursor as usize)..];
-        res
+        None
     }
 }
 
+/// Returns the raw bytes of the last non-blank line, including its terminator,
+/// so it can be carried over and re-parsed together with the next chunk.
+pub fn last_line(bytes: &[u8], dialect: CsvDialect) -> &[u8] {
+    let mut lines = LineSplitter::new(bytes, dialect);
+    let mut start = 0;
+    let mut rest = lines.bytes;
+
+    while lines.next().is_some() {
+        start = bytes.len() - rest.len();
+        rest = lines.bytes;
+    }
+
+    &bytes[start..]
+}
+
 pub struct FieldSplitter<'a> {
     bytes: &'a [u8],
-    del: u8,
+    dialect: CsvDialect,
     finish: bool,
 }
 
 impl<'a> FieldSplitter<'a> {
     pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes, CsvDialect::default())
+    }
+
+    pub fn new(bytes: &'a [u8], dialect: CsvDialect) -> Self {
         Self {
             bytes,
-            del: b',',
+            dialect,
             finish: false,
         }
     }
 }
 
 impl<'a> Iterator for FieldSplitter<'a> {
-    type Item = &'a [u8];
+    type Item = Cell<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.finish {
             return None;
         }
 
-        let mut cursor = 0i32;
-        let mut iter = self.bytes.iter();
-        let mut quoted = false;
-        let mut n_quotes = 0;
-
-        loop {
-            cursor += 1;
-            match iter.next() {
-                Some(&byte) if byte == b'"' => {
-                    if quoted {
-                        n_quotes += 1;
-                    }
-                    quoted = !quoted;
-                }
-                Some(&byte) if byte == self.del && !quoted => {
+        let mut scanner = Scanner::new(self.dialect);
+        let mut borrowed: Option<(usize, usize)> = None;
+        let mut owned: Option<Vec<u8>> = None;
+        let mut end = self.bytes.len();
+
+        for (i, &byte) in self.bytes.iter().enumerate() {
+            match scanner.advance(byte) {
+                Token::Delimiter => {
+                    end = i;
                     break;
                 }
-                None if !self.finish => {
-                    self.finish = !self.finish;
-                    return slice_bytes(self.bytes, n_quotes, cursor);
-                }
-                None => {
-                    return None;
-                }
-                _ => {
-                    continue;
-                }
+                Token::Skip => {}
+                Token::Data => match (owned.as_mut(), borrowed) {
+                    (Some(buff), _) => buff.push(byte),
+                    (None, Some((start, stop))) if stop == i => borrowed = Some((start, i + 1)),
+                    (None, Some((start, stop))) => {
+                        let mut buff = self.bytes[start..stop].to_vec();
+                        buff.push(byte);
+                        owned = Some(buff);
+                    }
+                    (None, None) => borrowed = Some((i, i + 1)),
+                },
             }
         }
 
-        let ret = slice_bytes(self.bytes, n_quotes, cursor);
-        self.bytes = &self.bytes[(cursor as usize)..];
-        ret
+        let cell = match (owned, borrowed) {
+            (Some(buff), _) => Cow::Owned(buff),
+            (None, Some((start, stop))) => Cow::Borrowed(&self.bytes[start..stop]),
+            (None, None) => Cow::Borrowed(&self.bytes[..0]),
+        };
+
+        if end == self.bytes.len() {
+            self.finish = true;
+        } else {
+            self.bytes = &self.bytes[end + 1..];
+        }
+
+        Some(cell)
     }
 }
 
@@ -127,10 +300,14 @@ impl<'a> FieldIter<'a> {
     pub fn from_bytes(bytes: &'a [u8]) -> Self {
         Self(FieldSplitter::from_bytes(bytes))
     }
+
+    pub fn new(bytes: &'a [u8], dialect: CsvDialect) -> Self {
+        Self(FieldSplitter::new(bytes, dialect))
+    }
 }
 
 impl<'a> Iterator for FieldIter<'a> {
-    type Item = &'a [u8];
+    type Item = Cell<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.0.next()
@@ -139,7 +316,9 @@ impl<'a> Iterator for FieldIter<'a> {
 
 #[cfg(test)]
 mod test {
-    use super::{to_str, FieldSplitter, LineSplitter};
+    use std::borrow::Cow;
+
+    use super::{last_line, to_str, CsvDialect, FieldSplitter, LineSplitter, LineTerminator};
 
     #[test]
     fn get_lines() {
@@ -165,30 +344,128 @@ Umbreon, Eevee, Dark, Gen II
         let data = "Espeon,Eevee,Psychic,Gen II";
         let mut field_splitter = FieldSplitter::from_bytes(data.as_bytes());
 
-        assert_eq!(to_str(field_splitter.next()), Some("Espeon"));
-        assert_eq!(to_str(field_splitter.next()), Some("Eevee"));
-        assert_eq!(to_str(field_splitter.next()), Some("Psychic"));
-        assert_eq!(to_str(field_splitter.next()), Some("Gen II"));
-        assert_eq!(to_str(field_splitter.next()), None);
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Espeon"));
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Eevee"));
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Psychic"));
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Gen II"));
+        assert_eq!(to_str(field_splitter.next().as_deref()), None);
 
         // Missing field
         let data = "Espeon,,Psychic,Gen II";
         let mut field_splitter = FieldSplitter::from_bytes(data.as_bytes());
 
-        assert_eq!(to_str(field_splitter.next()), Some("Espeon"));
-        assert_eq!(to_str(field_splitter.next()), Some(""));
-        assert_eq!(to_str(field_splitter.next()), Some("Psychic"));
-        assert_eq!(to_str(field_splitter.next()), Some("Gen II"));
-        assert_eq!(to_str(field_splitter.next()), None);
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Espeon"));
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some(""));
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Psychic"));
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Gen II"));
+        assert_eq!(to_str(field_splitter.next().as_deref()), None);
 
         // Delimiter inside a field
         let data = r#"Espeon,"Eevee, Friendship",Psychic,"Gen II, Number""#;
         let mut field_splitter = FieldSplitter::from_bytes(data.as_bytes());
 
-        assert_eq!(to_str(field_splitter.next()), Some(r#"Espeon"#));
-        assert_eq!(to_str(field_splitter.next()), Some("Eevee, Friendship"));
-        assert_eq!(to_str(field_splitter.next()), Some("Psychic"));
-        assert_eq!(to_str(field_splitter.next()), Some("Gen II, Number"));
-        assert_eq!(to_str(field_splitter.next()), None);
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some(r#"Espeon"#));
+        assert_eq!(
+            to_str(field_splitter.next().as_deref()),
+            Some("Eevee, Friendship")
+        );
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Psychic"));
+        assert_eq!(
+            to_str(field_splitter.next().as_deref()),
+            Some("Gen II, Number")
+        );
+        assert_eq!(to_str(field_splitter.next().as_deref()), None);
+    }
+
+    #[test]
+    fn get_lines_with_terminator() {
+        let data = "Espeon,Psychic\r\nUmbreon,Dark\r\n\r\nGlaceon,Ice\r";
+        let dialect = CsvDialect::new(b',', b'"', None, LineTerminator::CrLf);
+        let mut line_splitter = LineSplitter::new(data.as_bytes(), dialect);
+
+        assert_eq!(to_str(line_splitter.next()), Some("Espeon,Psychic"));
+        assert_eq!(to_str(line_splitter.next()), Some("Umbreon,Dark"));
+        assert_eq!(to_str(line_splitter.next()), Some("Glaceon,Ice"));
+        assert_eq!(to_str(line_splitter.next()), None);
+
+        let data = "Espeon,Psychic\rUmbreon,\"Dark\rType\"\r";
+        let dialect = CsvDialect::new(b',', b'"', None, LineTerminator::Cr);
+        let mut line_splitter = LineSplitter::new(data.as_bytes(), dialect);
+
+        assert_eq!(to_str(line_splitter.next()), Some("Espeon,Psychic"));
+        assert_eq!(to_str(line_splitter.next()), Some("Umbreon,\"Dark\rType\""));
+        assert_eq!(to_str(line_splitter.next()), None);
+        assert_eq!(
+            last_line(data.as_bytes(), dialect),
+            "Umbreon,\"Dark\rType\"\r".as_bytes()
+        );
+    }
+
+    #[test]
+    fn get_fields_with_dialect() {
+        let data = "Espeon\t'Eevee\tFriendship'\tPsy\\\tchic";
+        let dialect = CsvDialect::new(b'\t', b'\'', Some(b'\\'), LineTerminator::Any);
+        let mut field_splitter = FieldSplitter::new(data.as_bytes(), dialect);
+
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Espeon"));
+        assert_eq!(
+            to_str(field_splitter.next().as_deref()),
+            Some("Eevee\tFriendship")
+        );
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Psy\tchic"));
+        assert_eq!(to_str(field_splitter.next().as_deref()), None);
+    }
+
+    #[test]
+    fn get_escaped_fields() {
+        let data = r#""He said ""hi""",Mr. "Mime",Farfetch'd,"Sir""fetch'd" Jr."#;
+        let mut field_splitter = FieldSplitter::from_bytes(data.as_bytes());
+
+        assert_eq!(
+            to_str(field_splitter.next().as_deref()),
+            Some(r#"He said "hi""#)
+        );
+        assert_eq!(
+            to_str(field_splitter.next().as_deref()),
+            Some(r#"Mr. "Mime""#)
+        );
+        assert_eq!(to_str(field_splitter.next().as_deref()), Some("Farfetch'd"));
+        assert_eq!(
+            to_str(field_splitter.next().as_deref()),
+            Some(r#"Sir"fetch'd Jr."#)
+        );
+        assert_eq!(to_str(field_splitter.next().as_deref()), None);
+
+        // Cells without escapes are borrowed from the input
+        let data = r#""Eevee, Friendship",Psychic,"""#;
+        let mut field_splitter = FieldSplitter::from_bytes(data.as_bytes());
+
+        assert!(matches!(
+            field_splitter.next(),
+            Some(Cow::Borrowed(b"Eevee, Friendship"))
+        ));
+        assert!(matches!(
+            field_splitter.next(),
+            Some(Cow::Borrowed(b"Psychic"))
+        ));
+        assert!(matches!(field_splitter.next(), Some(Cow::Borrowed(b""))));
+        assert_eq!(field_splitter.next(), None);
+    }
+
+    #[test]
+    fn get_lines_with_quoted_newlines() {
+        let data = "Espeon,\"Sun\nPokemon\"\nUmbreon,\"Moon \"\"\n\"\"\"\nGlaceon,\"Ice";
+        let mut line_splitter = LineSplitter::from_bytes(data.as_bytes());
+
+        assert_eq!(
+            to_str(line_splitter.next()),
+            Some("Espeon,\"Sun\nPokemon\"")
+        );
+        assert_eq!(
+            to_str(line_splitter.next()),
+            Some("Umbreon,\"Moon \"\"\n\"\"\"")
+        );
+        assert_eq!(to_str(line_splitter.next()), Some("Glaceon,\"Ice"));
+        assert_eq!(to_str(line_splitter.next()), None);
     }
 }
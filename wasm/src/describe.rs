@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+
+use bitvec::prelude::BitSlice;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{column::Column, series::SeriesTrait, type_parser::Codes};
+
+/// Number of most frequent values kept for text columns.
+pub const TOP_VALUES: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NumericStats {
+    pub(crate) mean: f64,
+    /// Sample standard deviation, missing below two values.
+    pub(crate) std: Option<f64>,
+    pub(crate) min: f64,
+    pub(crate) q1: f64,
+    pub(crate) median: f64,
+    pub(crate) q3: f64,
+    pub(crate) max: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextStats {
+    /// Most frequent values first, ties in text order.
+    pub(crate) top: Vec<(String, usize)>,
+    pub(crate) min_length: usize,
+    pub(crate) max_length: usize,
+}
+
+/// Summary of a column, NaN is left out of the numeric statistics.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnStats {
+    pub(crate) column: String,
+    pub(crate) dtype: Codes,
+    pub(crate) count: usize,
+    pub(crate) null_count: usize,
+    pub(crate) distinct: usize,
+    pub(crate) numeric: Option<NumericStats>,
+    pub(crate) text: Option<TextStats>,
+}
+
+pub fn describe(column: &Column) -> ColumnStats {
+    let series = column.series();
+    let null_count = series.null_count();
+    let mut stats = ColumnStats {
+        column: column.name().into(),
+        dtype: column.dtype(),
+        count: series.len() - null_count,
+        null_count,
+        distinct: 0,
+        numeric: None,
+        text: None,
+    };
+
+    if let Some(mut values) = numbers(series, None) {
+        stats.distinct = values
+            .iter()
+            .map(|value| value.to_bits())
+            .collect::<HashSet<_>>()
+            .len();
+        stats.numeric = numeric_stats(&mut values);
+    } else if let Ok(values) = series.str() {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        values
+            .iter()
+            .flatten()
+            .for_each(|value| *counts.entry(value.as_str()).or_default() += 1);
+        stats.distinct = counts.len();
+        stats.text = text_stats(counts);
+    } else {
+        let words = series.words();
+        stats.distinct = words
+            .into_iter()
+            .filter(|word| !word.is_empty())
+            .collect::<HashSet<_>>()
+            .len();
+    }
+    stats
+}
+
+/// The values of a numeric series as floats, leaving out nulls, NaN and the rows not set
+/// in the mask when there is one. `None` for other series.
+pub fn numbers(series: &dyn SeriesTrait, mask: Option<&BitSlice>) -> Option<Vec<f64>> {
+    fn collect<T: Copy>(
+        values: &[Option<T>],
+        mask: Option<&BitSlice>,
+        conv: fn(T) -> f64,
+    ) -> Vec<f64> {
+        let kept = |row: usize| match mask {
+            Some(mask) => mask.get(row).is_some_and(|kept| *kept),
+            None => true,
+        };
+        let values = values.iter().enumerate().filter(|&(row, _)| kept(row));
+        let values = values.filter_map(|(_, value)| value.map(conv));
+        values.filter(|value| !value.is_nan()).collect()
+    }
+
+    if let Ok(values) = series.i32() {
+        Some(collect(values, mask, f64::from))
+    } else if let Ok(values) = series.i64() {
+        Some(collect(values, mask, |value| value as f64))
+    } else if let Ok(values) = series.i128() {
+        Some(collect(values, mask, |value| value as f64))
+    } else if let Ok(values) = series.f32() {
+        Some(collect(values, mask, f64::from))
+    } else if let Ok(values) = series.f64() {
+        Some(collect(values, mask, |value| value))
+    } else {
+        None
+    }
+}
+
+fn numeric_stats(values: &mut [f64]) -> Option<NumericStats> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+
+    // Welford's running mean and variance
+    let (mut mean, mut squares) = (0.0, 0.0);
+    for (i, &value) in values.iter().enumerate() {
+        let delta = value - mean;
+        mean += delta / (i + 1) as f64;
+        squares += delta * (value - mean);
+    }
+    let n = values.len();
+    let std = (n > 1).then(|| (squares / (n - 1) as f64).sqrt());
+
+    Some(NumericStats {
+        mean,
+        std,
+        min: values[0],
+        q1: quantile(values, 0.25),
+        median: quantile(values, 0.5),
+        q3: quantile(values, 0.75),
+        max: values[n - 1],
+    })
+}
+
+/// Linear interpolation between the two closest ranks of sorted values.
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (position - low as f64)
+}
+
+fn text_stats(counts: HashMap<&str, usize>) -> Option<TextStats> {
+    let lengths = counts.keys().map(|value| value.chars().count());
+    let min_length = lengths.clone().min()?;
+    let max_length = lengths.max()?;
+
+    let mut top: Vec<(&str, usize)> = counts.into_iter().collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    let top = top
+        .into_iter()
+        .take(TOP_VALUES)
+        .map(|(value, count)| (value.to_string(), count))
+        .collect();
+
+    Some(TextStats {
+        top,
+        min_length,
+        max_length,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::quantile;
+
+    #[test]
+    fn quantiles() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(quantile(&values, 0.0), 1.0);
+        assert_eq!(quantile(&values, 0.25), 1.75);
+        assert_eq!(quantile(&values, 0.5), 2.5);
+        assert_eq!(quantile(&values, 1.0), 4.0);
+        assert_eq!(quantile(&[7.0], 0.75), 7.0);
+    }
+}
@@ -0,0 +1,204 @@
+use core::fmt;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::type_parser::Codes;
+
+/// What to do with a row whose field count differs from the frame width.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RowPolicy {
+    /// Reject the whole chunk.
+    Error,
+    /// Drop the row.
+    Skip,
+    /// Fill missing fields with nulls and drop the extra ones.
+    #[default]
+    PadWithNulls,
+    /// Drop the extra fields, rows with missing fields are skipped.
+    Truncate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowAction {
+    Keep,
+    Skip,
+    Fail,
+}
+
+impl RowPolicy {
+    pub fn resolve(self, expected: usize, actual: usize) -> RowAction {
+        match self {
+            _ if expected == actual => RowAction::Keep,
+            RowPolicy::Error => RowAction::Fail,
+            RowPolicy::Skip => RowAction::Skip,
+            RowPolicy::PadWithNulls => RowAction::Keep,
+            RowPolicy::Truncate if actual > expected => RowAction::Keep,
+            RowPolicy::Truncate => RowAction::Skip,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub(crate) row: usize,
+    pub(crate) offset: usize,
+    pub(crate) expected: usize,
+    pub(crate) actual: usize,
+    pub(crate) line: String,
+}
+
+impl Diagnostic {
+    pub fn new(row: usize, offset: usize, expected: usize, actual: usize, line: &[u8]) -> Self {
+        Self {
+            row,
+            offset,
+            expected,
+            actual,
+            line: String::from_utf8_lossy(line).into_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Row {} at byte {}: expected {} fields, found {}",
+            self.row, self.offset, self.expected, self.actual
+        )
+    }
+}
+
+/// A column widened in place because a chunk held values its type could not represent.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Promotion {
+    pub(crate) column: String,
+    pub(crate) from: Codes,
+    pub(crate) to: Codes,
+    pub(crate) chunk: usize,
+}
+
+impl Promotion {
+    pub fn new(column: &str, from: Codes, to: Codes, chunk: usize) -> Self {
+        Self {
+            column: column.into(),
+            from,
+            to,
+            chunk,
+        }
+    }
+}
+
+/// A value that did not survive a column cast and was replaced by a null.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CastFailure {
+    pub(crate) row: usize,
+    pub(crate) value: String,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CastReport {
+    pub(crate) column: String,
+    pub(crate) from: Codes,
+    pub(crate) to: Codes,
+    pub(crate) failures: Vec<CastFailure>,
+}
+
+impl CastReport {
+    pub fn new(column: &str, from: Codes, to: Codes) -> Self {
+        Self {
+            column: column.into(),
+            from,
+            to,
+            failures: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: usize, value: &[u8]) {
+        self.failures.push(CastFailure {
+            row,
+            value: String::from_utf8_lossy(value).into_owned(),
+        });
+    }
+
+    pub fn failures(&self) -> &[CastFailure] {
+        &self.failures
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaError {
+    /// Types and dialect are fixed before the first chunk, types take a cast afterwards.
+    Loaded,
+    UnknownColumn(String),
+    UnknownType(String),
+    /// Only temporal types take a format.
+    Unformatted(Codes),
+    /// Only numeric columns have aggregates other than counts.
+    NotNumeric(String),
+    DuplicateColumn(String),
+    /// Computed columns take the type of their expression.
+    Computed(String),
+    /// Cells are written like `B2` or `R2C2`.
+    InvalidCell(String),
+    InvalidFormula(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Loaded => write!(f, "Schema cannot change once data is loaded"),
+            SchemaError::UnknownColumn(name) => write!(f, "Unknown column {}", name),
+            SchemaError::UnknownType(name) => write!(f, "Unknown type {}", name),
+            SchemaError::Unformatted(code) => write!(f, "Type {:?} takes no format", code),
+            SchemaError::NotNumeric(name) => write!(f, "Column {} is not numeric", name),
+            SchemaError::DuplicateColumn(name) => write!(f, "Column {} already exists", name),
+            SchemaError::Computed(name) => write!(f, "Column {} is computed", name),
+            SchemaError::InvalidCell(cell) => write!(f, "Invalid cell {}", cell),
+            SchemaError::InvalidFormula(text) => write!(f, "Invalid formula {}", text),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct ParseReport {
+    pub(crate) policy: RowPolicy,
+    pub(crate) rows: usize,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    pub(crate) promotions: Vec<Promotion>,
+}
+
+impl ParseReport {
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn promotions(&self) -> &[Promotion] {
+        &self.promotions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RowAction, RowPolicy};
+
+    #[test]
+    fn resolve() {
+        assert_eq!(RowPolicy::Error.resolve(3, 3), RowAction::Keep);
+        assert_eq!(RowPolicy::Error.resolve(3, 2), RowAction::Fail);
+        assert_eq!(RowPolicy::Skip.resolve(3, 4), RowAction::Skip);
+        assert_eq!(RowPolicy::PadWithNulls.resolve(3, 2), RowAction::Keep);
+        assert_eq!(RowPolicy::Truncate.resolve(3, 4), RowAction::Keep);
+        assert_eq!(RowPolicy::Truncate.resolve(3, 2), RowAction::Skip);
+    }
+}
@@ -1,11 +1,19 @@
+use core::{
+    fmt,
+    ops::{BitAnd, BitOr, Not},
+};
+
 use bitvec::{prelude::BitVec, slice::BitSlice};
-use js_sys::JsString;
+use regex::{Regex, RegexBuilder};
 use wasm_bindgen::prelude::wasm_bindgen;
 
 use crate::{
+    column::Column,
     csv_parser::FieldIter,
-    series::SeriesTrait,
-    type_parser::{parse_type, parse_utf8, Codes},
+    diagnostics::SchemaError,
+    series::{errors::WrongType, SeriesTrait},
+    slice::ColumnSlice,
+    type_parser::{parse_bool, parse_type, parse_utf8, Codes},
     Frame, Words,
 };
 
@@ -13,13 +21,134 @@ pub fn single_buffer_into_col_trait(bytes: &[u8], code: Codes) -> Box<dyn Series
     let mut commands = Words::default();
     let words = FieldIter::from_bytes(bytes);
     for word in words {
-        commands.extend(word);
+        commands.extend(&word);
     }
 
+    words_into_col_trait(commands, code)
+}
+
+pub fn words_into_col_trait(commands: Words, code: Codes) -> Box<dyn SeriesTrait> {
     match code {
+        Codes::Boolean => Box::new(parse_bool(commands)),
         Codes::Int32 => Box::new(parse_type::<i32>(commands)),
-        Codes::Any => Box::new(parse_utf8(commands)),
-        _ => panic!("Unimplemented"),
+        Codes::Int64 => Box::new(parse_type::<i64>(commands)),
+        Codes::Int128 => Box::new(parse_type::<i128>(commands)),
+        Codes::Float32 => Box::new(parse_type::<f32>(commands)),
+        Codes::Float64 => Box::new(parse_type::<f64>(commands)),
+        // Temporal values are parsed by the column itself, with its own format
+        _ => Box::new(parse_utf8(commands)),
+    }
+}
+
+/// Filters apply to the columns of the frame, of a type they know how to compare.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FilterError {
+    Schema(SchemaError),
+    WrongType,
+}
+
+impl From<SchemaError> for FilterError {
+    fn from(err: SchemaError) -> Self {
+        FilterError::Schema(err)
+    }
+}
+
+impl From<WrongType> for FilterError {
+    fn from(_: WrongType) -> Self {
+        FilterError::WrongType
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::Schema(err) => write!(f, "{}", err),
+            FilterError::WrongType => write!(f, "{}", WrongType),
+        }
+    }
+}
+
+/// Comparisons between a column and a single value, nulls never match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn test<T: PartialOrd + ?Sized>(self, left: &T, right: &T) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterEqual => left >= right,
+            Comparison::NotEqual => left != right,
+        }
+    }
+}
+
+/// The values a column is filtered against, written like the column, e.g. `$1,200`
+/// against a currency column.
+fn needle(frame: &Frame, col: &Column, values: &[&[u8]]) -> Box<dyn SeriesTrait> {
+    let mut words = Words::default();
+    for &bytes in values {
+        let normalized = col
+            .dtype()
+            .is_numeric()
+            .then(|| frame.numbers.normalize(bytes))
+            .flatten();
+        words.extend(
+            normalized
+                .as_ref()
+                .map_or(bytes, |(plain, _)| plain.as_bytes()),
+        );
+    }
+    words_into_col_trait(words, col.dtype())
+}
+
+/// Predicates on text columns.
+#[derive(Clone, Debug)]
+pub enum TextPredicate {
+    Contains(String),
+    StartsWith(String),
+    EndsWith(String),
+    Matches(Regex),
+}
+
+impl TextPredicate {
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(TextPredicate::Matches)
+    }
+
+    /// SQL `ILIKE`: `%` stands for any text, `_` for a single character, case is ignored.
+    pub fn ilike(pattern: &str) -> Result<Self, regex::Error> {
+        let mut ret = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '%' => ret.push_str(".*"),
+                '_' => ret.push('.'),
+                c => ret.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        ret.push('$');
+
+        RegexBuilder::new(&ret)
+            .case_insensitive(true)
+            .dot_matches_new_line(true)
+            .build()
+            .map(TextPredicate::Matches)
+    }
+
+    pub fn test(&self, text: &str) -> bool {
+        match self {
+            TextPredicate::Contains(needle) => text.contains(needle.as_str()),
+            TextPredicate::StartsWith(needle) => text.starts_with(needle.as_str()),
+            TextPredicate::EndsWith(needle) => text.ends_with(needle.as_str()),
+            TextPredicate::Matches(regex) => regex.is_match(text),
+        }
     }
 }
 
@@ -30,25 +159,147 @@ pub struct Filter {
 }
 
 impl Filter {
-    pub fn add_equalto_filter(&mut self, frame: &Frame, bytes: &[u8], column: &str) {
-        let col = frame.find_by_name(column);
-        let other = single_buffer_into_col_trait(bytes, col.dtype());
-        let mask = col.equal_to(other.as_ref()).unwrap();
+    pub fn add_equalto_filter(
+        &mut self,
+        frame: &Frame,
+        bytes: &[u8],
+        column: &str,
+    ) -> Result<(), FilterError> {
+        self.add_in_filter(frame, &[bytes], column)
+    }
+
+    /// Keeps the rows equal to any of the values.
+    pub fn add_in_filter(
+        &mut self,
+        frame: &Frame,
+        values: &[&[u8]],
+        column: &str,
+    ) -> Result<(), FilterError> {
+        let col = frame.column(column)?;
+        let other = needle(frame, col, values);
+        self.restrict(col.equal_to(other.as_ref(), frame.tolerance)?);
+
+        Ok(())
+    }
+
+    /// Keeps the rows between two values, each bound can be left out of the range.
+    pub fn add_between_filter(
+        &mut self,
+        frame: &Frame,
+        low: &[u8],
+        high: &[u8],
+        column: &str,
+        low_inclusive: bool,
+        high_inclusive: bool,
+    ) -> Result<(), FilterError> {
+        let (above, below) = match (low_inclusive, high_inclusive) {
+            (true, true) => (Comparison::GreaterEqual, Comparison::LessEqual),
+            (true, false) => (Comparison::GreaterEqual, Comparison::Less),
+            (false, true) => (Comparison::Greater, Comparison::LessEqual),
+            (false, false) => (Comparison::Greater, Comparison::Less),
+        };
+        self.add_comparison_filter(frame, low, column, above)?;
+        self.add_comparison_filter(frame, high, column, below)
+    }
+
+    pub fn add_comparison_filter(
+        &mut self,
+        frame: &Frame,
+        bytes: &[u8],
+        column: &str,
+        op: Comparison,
+    ) -> Result<(), FilterError> {
+        let col = frame.column(column)?;
+        let other = needle(frame, col, &[bytes]);
+        self.restrict(col.compare(other.as_ref(), op)?);
+
+        Ok(())
+    }
+
+    pub fn add_text_filter(
+        &mut self,
+        frame: &Frame,
+        column: &str,
+        predicate: &TextPredicate,
+    ) -> Result<(), FilterError> {
+        let col = frame.column(column)?;
+        self.restrict(col.series().matches(predicate)?);
+
+        Ok(())
+    }
+
+    /// Keeps the rows where the column is missing a value, or the others when `is_null` is false.
+    pub fn add_null_filter(
+        &mut self,
+        frame: &Frame,
+        column: &str,
+        is_null: bool,
+    ) -> Result<(), FilterError> {
+        let mask = frame.column(column)?.null_mask();
+        if is_null {
+            self.restrict(mask);
+        } else {
+            self.restrict(!mask);
+        }
+        Ok(())
+    }
 
-        self.filter = mask;
+    /// Keeps only the rows also kept by `mask`, a new filter takes the mask as is.
+    fn restrict(&mut self, mask: BitVec) {
+        if self.filter.is_empty() {
+            self.filter = mask;
+        } else {
+            self.filter &= mask.as_bitslice();
+        }
     }
 
-    fn get(&self) -> &BitSlice {
+    pub(crate) fn get(&self) -> &BitSlice {
         self.filter.as_bitslice()
     }
 
-    pub fn slice(&self, frame: &Frame, offset: usize, size: usize) -> Vec<JsString> {
+    /// Rows kept by the filter in the frame's order, counted among the kept ones.
+    /// Rows added to the frame after the filter was built are left out.
+    pub fn rows(&self, frame: &Frame, offset: usize, size: usize) -> Vec<usize> {
         let mask = self.get();
         frame
-            .columns
+            .index()
             .iter()
-            .map(|col| col.filter_join(mask, offset, size))
-            .map(|s| JsString::from(s.as_str()))
+            .copied()
+            .filter(|&row| mask.get(row).is_some_and(|kept| *kept))
+            .skip(offset)
+            .take(size)
             .collect()
     }
+
+    pub fn slice(&self, frame: &Frame, offset: usize, size: usize) -> Vec<ColumnSlice> {
+        frame.slice_rows(&self.rows(frame, offset, size))
+    }
+}
+
+impl BitAnd for Filter {
+    type Output = Filter;
+
+    fn bitand(mut self, rhs: Filter) -> Filter {
+        self.restrict(rhs.filter);
+        self
+    }
+}
+
+impl BitOr for Filter {
+    type Output = Filter;
+
+    fn bitor(mut self, rhs: Filter) -> Filter {
+        self.filter |= rhs.filter.as_bitslice();
+        self
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter {
+            filter: !self.filter,
+        }
+    }
 }
@@ -0,0 +1,292 @@
+use core::{cmp::Ordering, fmt, ops::Range};
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use super::{
+    functions::call,
+    parser::{BinaryOp, Formula},
+    reference::{CellRange, CellRef},
+};
+use crate::{column::Column, slice::widen_f32};
+
+/// Error values of a cell, they spread through every formula reading them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorValue {
+    Div0,
+    NA,
+    Value,
+    Ref,
+    Name,
+    Num,
+    /// The cell is part of a cycle of references or reads one.
+    Circular,
+}
+
+impl fmt::Display for ErrorValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            ErrorValue::Div0 => "#DIV/0!",
+            ErrorValue::NA => "#N/A",
+            ErrorValue::Value => "#VALUE!",
+            ErrorValue::Ref => "#REF!",
+            ErrorValue::Name => "#NAME?",
+            ErrorValue::Num => "#NUM!",
+            ErrorValue::Circular => "#CIRCULAR!",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Empty,
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    /// Counts as its serial number in arithmetic, days since 1899-12-30.
+    Date(NaiveDate),
+    Error(ErrorValue),
+}
+
+impl Value {
+    /// Numbers keep 15 significant digits, so `0.1 + 0.2` reads `0.3`.
+    pub fn text(&self) -> String {
+        match self {
+            Value::Empty => String::new(),
+            Value::Number(value) if value.is_finite() => format!("{:.14e}", value)
+                .parse::<f64>()
+                .map_or_else(|_| value.to_string(), |value| value.to_string()),
+            Value::Number(value) => value.to_string(),
+            Value::Text(value) => value.clone(),
+            Value::Bool(value) => value.to_string().to_ascii_uppercase(),
+            Value::Date(value) => value.format("%Y-%m-%d").to_string(),
+            Value::Error(error) => error.to_string(),
+        }
+    }
+
+    /// Empty cells count as zero and booleans as one or zero, text only when it reads as a number.
+    pub fn number(&self) -> Result<f64, ErrorValue> {
+        match self {
+            Value::Empty => Ok(0.0),
+            Value::Number(value) => Ok(*value),
+            Value::Bool(value) => Ok(f64::from(u8::from(*value))),
+            Value::Text(value) => value.trim().parse().map_err(|_| ErrorValue::Value),
+            Value::Date(value) => Ok(serial(*value)),
+            Value::Error(error) => Err(*error),
+        }
+    }
+
+    pub fn string(&self) -> Result<String, ErrorValue> {
+        match self {
+            Value::Error(error) => Err(*error),
+            value => Ok(value.text()),
+        }
+    }
+
+    /// Numbers and dates come before text and text before booleans, text ignores case.
+    pub fn compare(&self, other: &Value) -> Result<Ordering, ErrorValue> {
+        let rank = |value: &Value| match value {
+            Value::Number(_) => 0,
+            Value::Text(_) => 1,
+            _ => 2,
+        };
+        let (left, right) = match (self, other) {
+            (Value::Error(error), _) | (_, Value::Error(error)) => return Err(*error),
+            (Value::Empty, Value::Empty) => return Ok(Ordering::Equal),
+            (Value::Empty, other) => (other.blank(), other.clone()),
+            (value, Value::Empty) => (value.clone(), value.blank()),
+            (left, right) => (left.clone(), right.clone()),
+        };
+        Ok(match (&left.dated(), &right.dated()) {
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            (Value::Text(a), Value::Text(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (left, right) => rank(left).cmp(&rank(right)),
+        })
+    }
+
+    /// Dates as their serial number.
+    fn dated(&self) -> Value {
+        match self {
+            Value::Date(value) => Value::Number(serial(*value)),
+            value => value.clone(),
+        }
+    }
+
+    /// What an empty cell stands for next to this value.
+    fn blank(&self) -> Value {
+        match self {
+            Value::Text(_) => Value::Text(String::new()),
+            Value::Bool(_) => Value::Bool(false),
+            _ => Value::Number(0.0),
+        }
+    }
+}
+
+fn serial(date: NaiveDate) -> f64 {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30).expect("Valid date");
+    (date - epoch).num_days() as f64
+}
+
+/// Cells of a frame, formula cells give their last computed value.
+pub struct Cells<'a> {
+    columns: &'a [Column],
+    values: &'a HashMap<CellRef, Value>,
+}
+
+impl<'a> Cells<'a> {
+    pub fn new(columns: &'a [Column], values: &'a HashMap<CellRef, Value>) -> Self {
+        Self { columns, values }
+    }
+
+    /// Cells past the data are empty.
+    pub fn value(&self, cell: CellRef) -> Value {
+        match self.values.get(&cell) {
+            Some(computed) => computed.clone(),
+            None => self
+                .columns
+                .get(cell.column)
+                .map_or(Value::Empty, |column| column_value(column, cell.row)),
+        }
+    }
+
+    /// Rows of the range down to the last one holding data or a formula, the cells
+    /// below are empty.
+    pub fn filled(&self, range: CellRange) -> Range<usize> {
+        let height = self.columns.iter().map(Column::len).max().unwrap_or(0);
+        let formulas = self
+            .values
+            .keys()
+            .filter(|cell| (range.start.column..=range.end.column).contains(&cell.column))
+            .map(|cell| cell.row + 1)
+            .max()
+            .unwrap_or(0);
+        range.start.row..(range.end.row + 1).min(height.max(formulas))
+    }
+
+    /// Values of the filled rows of the range, row by row.
+    pub fn range(&self, range: CellRange) -> impl Iterator<Item = Value> + '_ {
+        let columns = range.start.column..=range.end.column;
+        self.filled(range).flat_map(move |row| {
+            columns
+                .clone()
+                .map(move |column| self.value(CellRef::new(row, column)))
+        })
+    }
+
+    pub fn evaluate(&self, formula: &Formula) -> Value {
+        match formula {
+            Formula::Number(value) => Value::Number(*value),
+            Formula::Text(value) => Value::Text(value.clone()),
+            Formula::Bool(value) => Value::Bool(*value),
+            Formula::Cell(cell) => self.value(*cell),
+            Formula::Range(range) if range.height() * range.width() == 1 => self.value(range.start),
+            Formula::Range(_) => Value::Error(ErrorValue::Value),
+            Formula::Neg(inner) => match self.evaluate(inner).number() {
+                Ok(value) => Value::Number(-value),
+                Err(error) => Value::Error(error),
+            },
+            Formula::Binary(op, left, right) => {
+                let (left, right) = (self.evaluate(left), self.evaluate(right));
+                binary(*op, &left, &right).unwrap_or_else(Value::Error)
+            }
+            Formula::Call(name, args) => call(self, name, args),
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, ErrorValue> {
+    let ordering = || left.compare(right);
+    let number = match op {
+        BinaryOp::Concat => return Ok(Value::Text(left.string()? + &right.string()?)),
+        BinaryOp::Eq => return Ok(Value::Bool(ordering()?.is_eq())),
+        BinaryOp::Ne => return Ok(Value::Bool(ordering()?.is_ne())),
+        BinaryOp::Lt => return Ok(Value::Bool(ordering()?.is_lt())),
+        BinaryOp::Le => return Ok(Value::Bool(ordering()?.is_le())),
+        BinaryOp::Gt => return Ok(Value::Bool(ordering()?.is_gt())),
+        BinaryOp::Ge => return Ok(Value::Bool(ordering()?.is_ge())),
+        BinaryOp::Add => left.number()? + right.number()?,
+        BinaryOp::Sub => left.number()? - right.number()?,
+        BinaryOp::Mul => left.number()? * right.number()?,
+        BinaryOp::Div => match (left.number()?, right.number()?) {
+            (_, 0.0) => return Err(ErrorValue::Div0),
+            (a, b) => a / b,
+        },
+        BinaryOp::Pow => left.number()?.powf(right.number()?),
+    };
+    if number.is_finite() {
+        Ok(Value::Number(number))
+    } else {
+        Err(ErrorValue::Num)
+    }
+}
+
+/// Data of a column at the given row, nulls and rows past the data are empty.
+fn column_value(column: &Column, row: usize) -> Value {
+    fn read<T>(values: &[Option<T>], row: usize, wrap: impl Fn(&T) -> Value) -> Value {
+        values
+            .get(row)
+            .and_then(Option::as_ref)
+            .map_or(Value::Empty, wrap)
+    }
+
+    let series = column.series();
+    if let Ok(values) = series.i32() {
+        read(values, row, |&value| Value::Number(value.into()))
+    } else if let Ok(values) = series.i64() {
+        read(values, row, |&value| Value::Number(value as f64))
+    } else if let Ok(values) = series.i128() {
+        read(values, row, |&value| Value::Number(value as f64))
+    } else if let Ok(values) = series.f32() {
+        read(values, row, |&value| Value::Number(widen_f32(value)))
+    } else if let Ok(values) = series.f64() {
+        read(values, row, |&value| Value::Number(value))
+    } else if let Ok(values) = series.bool() {
+        read(values, row, |&value| Value::Bool(value))
+    } else if let Ok(values) = series.date() {
+        read(values, row, |&value| Value::Date(value))
+    } else if let Ok(values) = series.str() {
+        read(values, row, |value| Value::Text(value.clone()))
+    } else {
+        match column.text(row, 1).pop() {
+            Some(text) if !text.is_empty() => Value::Text(text),
+            _ => Value::Empty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{Cells, ErrorValue, Value};
+    use crate::formula::{parser::parse_formula, reference::CellRef};
+
+    #[test]
+    fn evaluate() {
+        let values = HashMap::from([
+            (CellRef::new(0, 0), Value::Number(0.1)),
+            (CellRef::new(1, 0), Value::Number(0.2)),
+            (CellRef::new(2, 0), Value::Text("x".into())),
+            (CellRef::new(0, 1), Value::Error(ErrorValue::NA)),
+        ]);
+        let cells = Cells::new(&[], &values);
+        let eval = |text| cells.evaluate(&parse_formula(text, CellRef::new(0, 2)).unwrap());
+
+        assert_eq!(eval("=A1+A2").text(), "0.3");
+        assert_eq!(eval("=SUM(A1:A5, 1)").text(), "1.3");
+        assert_eq!(eval("=-2^2"), Value::Number(4.0));
+        assert_eq!(eval("=1/(A4)"), Value::Error(ErrorValue::Div0));
+        assert_eq!(eval("=A3*2"), Value::Error(ErrorValue::Value));
+        assert_eq!(eval("=A3&A1&TRUE"), Value::Text("x0.1TRUE".into()));
+        assert_eq!(eval("=A3=\"X\""), Value::Bool(true));
+        assert_eq!(eval("=A3<1"), Value::Bool(false));
+        assert_eq!(eval("=A5=0"), Value::Bool(true));
+        assert_eq!(eval("=B1+1"), Value::Error(ErrorValue::NA));
+        assert_eq!(eval("=SUM(A1:B1)"), Value::Error(ErrorValue::NA));
+        assert_eq!(eval("=A1:A2"), Value::Error(ErrorValue::Value));
+        assert_eq!(eval("=NOPE(1)"), Value::Error(ErrorValue::Name));
+    }
+}
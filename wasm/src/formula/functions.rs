@@ -0,0 +1,714 @@
+use core::cmp::Ordering;
+
+use chrono::NaiveDate;
+
+use super::{
+    eval::{Cells, ErrorValue, Value},
+    parser::{BinaryOp, Formula},
+    reference::{CellRange, CellRef},
+};
+
+/// Calls a function by its upper case name. Arguments are evaluated as the function
+/// needs them, so `IF` and `IFERROR` leave the branch they do not take alone.
+pub fn call(cells: &Cells, name: &str, args: &[Formula]) -> Value {
+    let result = match name {
+        "SUM" => numbers(cells, args).map(|numbers| Value::Number(numbers.iter().sum())),
+        "AVERAGE" => average(cells, args),
+        "COUNT" => Ok(count(cells, args)),
+        "COUNTA" => Ok(count_all(cells, args)),
+        "MIN" => extreme(cells, args, f64::min),
+        "MAX" => extreme(cells, args, f64::max),
+        "IF" => condition(cells, args),
+        "IFERROR" => arity(args, 2, 2).map(|_| match cells.evaluate(&args[0]) {
+            Value::Error(_) => cells.evaluate(&args[1]),
+            value => value,
+        }),
+        "AND" => logicals(cells, args).map(|values| Value::Bool(values.iter().all(|&v| v))),
+        "OR" => logicals(cells, args).map(|values| Value::Bool(values.iter().any(|&v| v))),
+        "ROUND" => round(cells, args),
+        "ABS" => arity(args, 1, 1)
+            .and_then(|_| number(cells, &args[0]))
+            .map(|value| Value::Number(value.abs())),
+        "CONCAT" => concat(cells, args),
+        "LEFT" | "RIGHT" => side(cells, args, name == "LEFT"),
+        "MID" => mid(cells, args),
+        "LEN" => text_function(cells, args, |text| {
+            Value::Number(text.chars().count() as f64)
+        }),
+        "UPPER" => text_function(cells, args, |text| Value::Text(text.to_uppercase())),
+        "LOWER" => text_function(cells, args, |text| Value::Text(text.to_lowercase())),
+        "TRIM" => text_function(cells, args, |text| {
+            Value::Text(
+                text.split(' ')
+                    .filter(|word| !word.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            )
+        }),
+        "VLOOKUP" => vlookup(cells, args),
+        "XLOOKUP" => xlookup(cells, args),
+        "SUMIF" => sum_if(cells, args),
+        "COUNTIF" => count_if(cells, args),
+        "TODAY" => arity(args, 0, 0).map(|_| Value::Date(today())),
+        _ => Err(ErrorValue::Name),
+    };
+    result.unwrap_or_else(Value::Error)
+}
+
+fn arity(args: &[Formula], min: usize, max: usize) -> Result<(), ErrorValue> {
+    if (min..=max).contains(&args.len()) {
+        Ok(())
+    } else {
+        Err(ErrorValue::Value)
+    }
+}
+
+/// The cells behind a reference argument, `None` for other arguments.
+fn reference(arg: &Formula) -> Option<CellRange> {
+    match arg {
+        Formula::Cell(cell) => Some(CellRange::new(*cell, *cell)),
+        Formula::Range(range) => Some(*range),
+        _ => None,
+    }
+}
+
+fn number(cells: &Cells, arg: &Formula) -> Result<f64, ErrorValue> {
+    cells.evaluate(arg).number()
+}
+
+fn text(cells: &Cells, arg: &Formula) -> Result<String, ErrorValue> {
+    cells.evaluate(arg).string()
+}
+
+/// Whole number argument, decimals are dropped.
+fn integer(cells: &Cells, arg: &Formula) -> Result<i64, ErrorValue> {
+    Ok(number(cells, arg)?.trunc() as i64)
+}
+
+/// Numbers of the arguments, text, booleans and empty cells of references are left out
+/// while other arguments have to read as numbers.
+fn numbers(cells: &Cells, args: &[Formula]) -> Result<Vec<f64>, ErrorValue> {
+    let mut numbers = Vec::new();
+    for arg in args {
+        match reference(arg) {
+            Some(range) => {
+                for value in cells.range(range) {
+                    match value {
+                        Value::Number(_) | Value::Date(_) => numbers.push(value.number()?),
+                        Value::Error(error) => return Err(error),
+                        _ => (),
+                    }
+                }
+            }
+            None => numbers.push(number(cells, arg)?),
+        }
+    }
+    Ok(numbers)
+}
+
+fn average(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    let numbers = numbers(cells, args)?;
+    if numbers.is_empty() {
+        return Err(ErrorValue::Div0);
+    }
+    Ok(Value::Number(
+        numbers.iter().sum::<f64>() / numbers.len() as f64,
+    ))
+}
+
+/// Smallest or largest number, zero without any.
+fn extreme(
+    cells: &Cells,
+    args: &[Formula],
+    pick: fn(f64, f64) -> f64,
+) -> Result<Value, ErrorValue> {
+    let numbers = numbers(cells, args)?;
+    Ok(Value::Number(
+        numbers.into_iter().reduce(pick).unwrap_or(0.0),
+    ))
+}
+
+/// Numbers among the values, errors are not counted.
+fn count(cells: &Cells, args: &[Formula]) -> Value {
+    let count: usize = args
+        .iter()
+        .map(|arg| match reference(arg) {
+            Some(range) => cells
+                .range(range)
+                .filter(|value| matches!(value, Value::Number(_) | Value::Date(_)))
+                .count(),
+            None => usize::from(number(cells, arg).is_ok()),
+        })
+        .sum();
+    Value::Number(count as f64)
+}
+
+/// Values that are not empty, errors included.
+fn count_all(cells: &Cells, args: &[Formula]) -> Value {
+    let count: usize = args
+        .iter()
+        .map(|arg| match reference(arg) {
+            Some(range) => cells
+                .range(range)
+                .filter(|value| *value != Value::Empty)
+                .count(),
+            None => 1,
+        })
+        .sum();
+    Value::Number(count as f64)
+}
+
+fn logical(value: &Value) -> Result<bool, ErrorValue> {
+    match value {
+        Value::Empty => Ok(false),
+        Value::Bool(value) => Ok(*value),
+        Value::Number(value) => Ok(*value != 0.0),
+        Value::Date(_) => Ok(true),
+        Value::Text(text) if text.eq_ignore_ascii_case("TRUE") => Ok(true),
+        Value::Text(text) if text.eq_ignore_ascii_case("FALSE") => Ok(false),
+        Value::Text(_) => Err(ErrorValue::Value),
+        Value::Error(error) => Err(*error),
+    }
+}
+
+/// Booleans of the arguments, text and empty cells of references are left out.
+fn logicals(cells: &Cells, args: &[Formula]) -> Result<Vec<bool>, ErrorValue> {
+    let mut logicals = Vec::new();
+    for arg in args {
+        match reference(arg) {
+            Some(range) => {
+                for value in cells.range(range) {
+                    match value {
+                        Value::Bool(_) | Value::Number(_) | Value::Error(_) => {
+                            logicals.push(logical(&value)?)
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            None => logicals.push(logical(&cells.evaluate(arg))?),
+        }
+    }
+    if logicals.is_empty() {
+        return Err(ErrorValue::Value);
+    }
+    Ok(logicals)
+}
+
+/// `IF(condition, then, else)`, a missing else gives `FALSE`.
+fn condition(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    arity(args, 2, 3)?;
+    if logical(&cells.evaluate(&args[0]))? {
+        Ok(cells.evaluate(&args[1]))
+    } else {
+        Ok(args
+            .get(2)
+            .map_or(Value::Bool(false), |arg| cells.evaluate(arg)))
+    }
+}
+
+/// Halves round away from zero, on the decimal value rather than its binary approximation.
+fn round(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    arity(args, 2, 2)?;
+    let value = number(cells, &args[0])?;
+    let digits = integer(cells, &args[1])?.clamp(-308, 308) as i32;
+    let factor = 10f64.powi(digits);
+    let scaled: f64 = format!("{:.14e}", value * factor)
+        .parse()
+        .map_err(|_| ErrorValue::Num)?;
+    let rounded = scaled.round() / factor;
+    if rounded.is_finite() {
+        Ok(Value::Number(rounded))
+    } else {
+        Err(ErrorValue::Num)
+    }
+}
+
+fn concat(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    let mut joined = String::new();
+    for arg in args {
+        match reference(arg) {
+            Some(range) => {
+                for value in cells.range(range) {
+                    joined.push_str(&value.string()?);
+                }
+            }
+            None => joined.push_str(&text(cells, arg)?),
+        }
+    }
+    Ok(Value::Text(joined))
+}
+
+fn text_function(
+    cells: &Cells,
+    args: &[Formula],
+    apply: impl Fn(&str) -> Value,
+) -> Result<Value, ErrorValue> {
+    arity(args, 1, 1)?;
+    Ok(apply(&text(cells, &args[0])?))
+}
+
+/// `LEFT(text, count)` and `RIGHT(text, count)`, one character by default.
+fn side(cells: &Cells, args: &[Formula], left: bool) -> Result<Value, ErrorValue> {
+    arity(args, 1, 2)?;
+    let text = text(cells, &args[0])?;
+    let count = match args.get(1) {
+        Some(arg) => usize::try_from(integer(cells, arg)?).map_err(|_| ErrorValue::Value)?,
+        None => 1,
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let count = count.min(chars.len());
+    let kept = if left {
+        &chars[..count]
+    } else {
+        &chars[chars.len() - count..]
+    };
+    Ok(Value::Text(kept.iter().collect()))
+}
+
+/// `MID(text, start, count)`, the first character is at 1.
+fn mid(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    arity(args, 3, 3)?;
+    let text = text(cells, &args[0])?;
+    let start = usize::try_from(integer(cells, &args[1])?).map_err(|_| ErrorValue::Value)?;
+    let count = usize::try_from(integer(cells, &args[2])?).map_err(|_| ErrorValue::Value)?;
+    if start == 0 {
+        return Err(ErrorValue::Value);
+    }
+    Ok(Value::Text(
+        text.chars().skip(start - 1).take(count).collect(),
+    ))
+}
+
+/// Whether both values are compared by the lookups, numbers only match numbers and so on.
+fn same_kind(a: &Value, b: &Value) -> bool {
+    let kind = |value: &Value| match value {
+        Value::Number(_) | Value::Date(_) => 0,
+        Value::Text(_) => 1,
+        Value::Bool(_) => 2,
+        Value::Empty => 3,
+        Value::Error(_) => 4,
+    };
+    kind(a) == kind(b) && kind(a) < 3
+}
+
+fn equal(a: &Value, b: &Value) -> bool {
+    same_kind(a, b) && a.compare(b) == Ok(Ordering::Equal)
+}
+
+/// `VLOOKUP(value, table, column, approximate)`. The approximate match, the default, takes
+/// the last row before the first key larger than the value, keys should be sorted.
+fn vlookup(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    arity(args, 3, 4)?;
+    let needle = cells.evaluate(&args[0]);
+    if let Value::Error(error) = needle {
+        return Err(error);
+    }
+    let table = reference(&args[1]).ok_or(ErrorValue::Value)?;
+    let column = integer(cells, &args[2])?;
+    let approximate = match args.get(3) {
+        Some(arg) => logical(&cells.evaluate(arg))?,
+        None => true,
+    };
+    if column < 1 {
+        return Err(ErrorValue::Value);
+    }
+    let column = column as usize - 1;
+    if column >= table.width() {
+        return Err(ErrorValue::Ref);
+    }
+
+    let values: Vec<Value> = cells.range(table).collect();
+    let mut rows = values.chunks(table.width());
+    let found = if approximate {
+        let mut found = None;
+        for row in rows {
+            let key = &row[0];
+            if !same_kind(key, &needle) {
+                continue;
+            }
+            if key.compare(&needle)? == Ordering::Greater {
+                break;
+            }
+            found = Some(row);
+        }
+        found
+    } else {
+        rows.find(|row| equal(&row[0], &needle))
+    };
+    found.map(|row| row[column].clone()).ok_or(ErrorValue::NA)
+}
+
+/// `XLOOKUP(value, keys, results, if_not_found)`, an exact match over a single row or column.
+fn xlookup(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    arity(args, 3, 4)?;
+    let needle = cells.evaluate(&args[0]);
+    if let Value::Error(error) = needle {
+        return Err(error);
+    }
+    let keys = reference(&args[1]).ok_or(ErrorValue::Value)?;
+    let results = reference(&args[2]).ok_or(ErrorValue::Value)?;
+    let line = |range: CellRange| range.height() == 1 || range.width() == 1;
+    let size = |range: CellRange| range.height() * range.width();
+    if !line(keys) || !line(results) || size(keys) != size(results) {
+        return Err(ErrorValue::Value);
+    }
+
+    let found = cells.range(keys).position(|key| equal(&key, &needle));
+    let result = |position| match results.width() {
+        1 => CellRef::new(results.start.row + position, results.start.column),
+        _ => CellRef::new(results.start.row, results.start.column + position),
+    };
+    match (found, args.get(3)) {
+        (Some(position), _) => Ok(cells.value(result(position))),
+        (None, Some(fallback)) => Ok(cells.evaluate(fallback)),
+        (None, None) => Err(ErrorValue::NA),
+    }
+}
+
+/// Condition of `SUMIF` and `COUNTIF`, a value or text like `">=10"`, `"<>Fire"` or `"Char*"`.
+#[derive(Clone, Debug, PartialEq)]
+struct Criterion {
+    op: BinaryOp,
+    value: Value,
+}
+
+impl Criterion {
+    fn new(value: Value) -> Result<Self, ErrorValue> {
+        let text = match value {
+            Value::Text(text) => text,
+            Value::Error(error) => return Err(error),
+            value => {
+                return Ok(Self {
+                    op: BinaryOp::Eq,
+                    value,
+                })
+            }
+        };
+        let (op, operand) = ["<>", "<=", ">=", "<", ">", "="]
+            .into_iter()
+            .find_map(|symbol| Some((symbol, text.strip_prefix(symbol)?)))
+            .map_or((BinaryOp::Eq, text.as_str()), |(symbol, operand)| {
+                (Self::operator(symbol), operand)
+            });
+        let value = if let Ok(number) = operand.trim().parse() {
+            Value::Number(number)
+        } else if operand.eq_ignore_ascii_case("TRUE") || operand.eq_ignore_ascii_case("FALSE") {
+            Value::Bool(operand.eq_ignore_ascii_case("TRUE"))
+        } else {
+            Value::Text(operand.into())
+        };
+        Ok(Self { op, value })
+    }
+
+    fn operator(symbol: &str) -> BinaryOp {
+        match symbol {
+            "<>" => BinaryOp::Ne,
+            "<=" => BinaryOp::Le,
+            ">=" => BinaryOp::Ge,
+            "<" => BinaryOp::Lt,
+            ">" => BinaryOp::Gt,
+            _ => BinaryOp::Eq,
+        }
+    }
+
+    /// Values of another kind only meet `<>`, an empty text matches empty cells.
+    fn matches(&self, cell: &Value) -> bool {
+        let ordering = match (&self.value, cell) {
+            (Value::Text(pattern), Value::Empty) if pattern.is_empty() => Ordering::Equal,
+            (Value::Text(pattern), Value::Text(text))
+                if matches!(self.op, BinaryOp::Eq | BinaryOp::Ne) =>
+            {
+                if wildcard(pattern, text) {
+                    Ordering::Equal
+                } else {
+                    Ordering::Less
+                }
+            }
+            (value, cell) if same_kind(value, cell) => match cell.compare(value) {
+                Ok(ordering) => ordering,
+                Err(_) => return false,
+            },
+            _ => return self.op == BinaryOp::Ne,
+        };
+        match self.op {
+            BinaryOp::Ne => ordering.is_ne(),
+            BinaryOp::Lt => ordering.is_lt(),
+            BinaryOp::Le => ordering.is_le(),
+            BinaryOp::Gt => ordering.is_gt(),
+            BinaryOp::Ge => ordering.is_ge(),
+            _ => ordering.is_eq(),
+        }
+    }
+}
+
+/// `*` stands for any text and `?` for any character, `~` keeps the next one as is.
+/// Case is ignored.
+fn wildcard(pattern: &str, text: &str) -> bool {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Token {
+        Any,
+        One,
+        Char(char),
+    }
+
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars().flat_map(char::to_lowercase);
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '*' => Token::Any,
+            '?' => Token::One,
+            '~' => Token::Char(chars.next().unwrap_or('~')),
+            c => Token::Char(c),
+        });
+    }
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+
+    // Greedy match going back to the last `*` on a mismatch
+    let (mut t, mut p) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(Token::One) => (t, p) = (t + 1, p + 1),
+            Some(Token::Char(c)) if *c == text[t] => (t, p) = (t + 1, p + 1),
+            Some(Token::Any) => {
+                star = Some((p, t));
+                p += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    (t, p) = (star_t + 1, star_p + 1);
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[p..].iter().all(|token| *token == Token::Any)
+}
+
+/// Pairs of the range cells and the values at the same positions of `values`, which is
+/// resized to the range from its top left cell. Rows past the data of both are left out.
+fn criteria_pairs<'a>(
+    cells: &'a Cells,
+    range: CellRange,
+    values: Option<CellRange>,
+) -> impl Iterator<Item = (Value, Value)> + 'a {
+    let values = values.map_or(range, |values| {
+        let end = CellRef::new(
+            values.start.row + range.height() - 1,
+            values.start.column + range.width() - 1,
+        );
+        CellRange::new(values.start, end)
+    });
+    let height = cells.filled(range).len().max(cells.filled(values).len());
+    (0..height).flat_map(move |row| {
+        (0..range.width()).map(move |column| {
+            let at = |range: CellRange| {
+                cells.value(CellRef::new(
+                    range.start.row + row,
+                    range.start.column + column,
+                ))
+            };
+            (at(range), at(values))
+        })
+    })
+}
+
+/// `SUMIF(range, criterion, sum_range)`, the range itself is summed when there is no sum range.
+fn sum_if(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    arity(args, 2, 3)?;
+    let range = reference(&args[0]).ok_or(ErrorValue::Value)?;
+    let criterion = Criterion::new(cells.evaluate(&args[1]))?;
+    let values = match args.get(2) {
+        Some(arg) => Some(reference(arg).ok_or(ErrorValue::Value)?),
+        None => None,
+    };
+    let mut sum = 0.0;
+    for (key, value) in criteria_pairs(cells, range, values) {
+        if criterion.matches(&key) {
+            match value {
+                Value::Number(_) | Value::Date(_) => sum += value.number()?,
+                Value::Error(error) => return Err(error),
+                _ => (),
+            }
+        }
+    }
+    Ok(Value::Number(sum))
+}
+
+fn count_if(cells: &Cells, args: &[Formula]) -> Result<Value, ErrorValue> {
+    arity(args, 2, 2)?;
+    let range = reference(&args[0]).ok_or(ErrorValue::Value)?;
+    let criterion = Criterion::new(cells.evaluate(&args[1]))?;
+    let count = cells
+        .range(range)
+        .filter(|value| criterion.matches(value))
+        .count();
+    // Cells past the data are empty, they are counted without being read
+    let filled = cells.filled(range).len() * range.width();
+    let empty = range.height().saturating_mul(range.width()) - filled;
+    let count = count
+        + if criterion.matches(&Value::Empty) {
+            empty
+        } else {
+            0
+        };
+    Ok(Value::Number(count as f64))
+}
+
+/// The local date in the browser, the UTC one elsewhere.
+fn today() -> NaiveDate {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let now = js_sys::Date::new_0();
+        NaiveDate::from_ymd_opt(
+            now.get_full_year() as i32,
+            now.get_month() + 1,
+            now.get_date(),
+        )
+        .unwrap_or_default()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() / 86_400);
+        NaiveDate::default()
+            .checked_add_days(chrono::Days::new(days))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{wildcard, Criterion};
+    use crate::formula::{
+        eval::{Cells, ErrorValue, Value},
+        parser::parse_formula,
+        reference::CellRef,
+    };
+
+    #[test]
+    fn functions() {
+        let number = Value::Number;
+        let text = |value: &str| Value::Text(value.into());
+        let rows = [
+            [text("Bulbasaur"), text("Grass"), number(45.0)],
+            [text("Charmander"), text("Fire"), number(39.0)],
+            [text("Squirtle"), text("Water"), number(44.0)],
+            [text("Charizard"), text("Fire"), Value::Empty],
+            [
+                text("  Mr.  Mime "),
+                Value::Bool(true),
+                Value::Error(ErrorValue::NA),
+            ],
+        ];
+        let values: HashMap<CellRef, Value> = rows
+            .into_iter()
+            .enumerate()
+            .flat_map(|(row, values)| {
+                values
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(column, value)| (CellRef::new(row, column), value))
+            })
+            .collect();
+        let cells = Cells::new(&[], &values);
+        let eval = |text| cells.evaluate(&parse_formula(text, CellRef::new(0, 5)).unwrap());
+
+        assert_eq!(eval("=SUM(C1:C4, \"1\")"), number(129.0));
+        assert_eq!(eval("=AVERAGE(C1:C4)"), number(128.0 / 3.0));
+        assert_eq!(eval("=AVERAGE(D1:D4)"), Value::Error(ErrorValue::Div0));
+        assert_eq!(eval("=SUM(C1:C5)"), Value::Error(ErrorValue::NA));
+        assert_eq!(eval("=COUNT(A1:C5, 2, \"x\")"), number(4.0));
+        assert_eq!(eval("=COUNTA(A1:C5)"), number(14.0));
+        assert_eq!(
+            (eval("=MIN(C1:C4)"), eval("=MAX(D1:D4)")),
+            (number(39.0), number(0.0))
+        );
+
+        assert_eq!(eval("=IF(C1>40, \"big\", 1/0)"), text("big"));
+        assert_eq!(eval("=IF(C2>40, 1)"), Value::Bool(false));
+        assert_eq!(eval("=IF(A1, 1, 2)"), Value::Error(ErrorValue::Value));
+        assert_eq!(eval("=IFERROR(C5, \"none\")"), text("none"));
+        assert_eq!(eval("=AND(B5, C1>40)"), Value::Bool(true));
+        assert_eq!(eval("=OR(A1:A3)"), Value::Error(ErrorValue::Value));
+        assert_eq!(eval("=OR(FALSE, 0, B5)"), Value::Bool(true));
+
+        assert_eq!(eval("=ROUND(2.675, 2)"), number(2.68));
+        assert_eq!(eval("=ROUND(-1250, -2)"), number(-1300.0));
+        assert_eq!(eval("=ABS(-C1)"), number(45.0));
+        assert_eq!(eval("=CONCAT(B1:B2, \"-\", 1)"), text("GrassFire-1"));
+        assert_eq!(
+            eval("=LEFT(A2, 4)&RIGHT(A3)&MID(A1, 5, 3)"),
+            text("Chareasa")
+        );
+        assert_eq!(eval("=LEFT(A2, -1)"), Value::Error(ErrorValue::Value));
+        assert_eq!(eval("=LEN(A1)+LEN(D1)"), number(9.0));
+        assert_eq!(eval("=UPPER(B1)&LOWER(B2)"), text("GRASSfire"));
+        assert_eq!(eval("=TRIM(A5)"), text("Mr. Mime"));
+
+        assert_eq!(
+            eval("=VLOOKUP(\"squirtle\", A1:C5, 3, FALSE)"),
+            number(44.0)
+        );
+        assert_eq!(
+            eval("=VLOOKUP(\"Mew\", A1:C5, 2, FALSE)"),
+            Value::Error(ErrorValue::NA)
+        );
+        assert_eq!(eval("=VLOOKUP(40, C2:C3, 1)"), number(39.0));
+        assert_eq!(eval("=VLOOKUP(50, C2:C3, 1)"), number(44.0));
+        assert_eq!(eval("=VLOOKUP(30, C2:C3, 1)"), Value::Error(ErrorValue::NA));
+        assert_eq!(
+            eval("=VLOOKUP(\"Bulbasaur\", A1:C5, 4)"),
+            Value::Error(ErrorValue::Ref)
+        );
+        assert_eq!(eval("=XLOOKUP(44, C1:C5, A1:A5)"), text("Squirtle"));
+        assert_eq!(eval("=XLOOKUP(1, C1:C5, A1:A5, \"-\")"), text("-"));
+        assert_eq!(
+            eval("=XLOOKUP(1, C1:C5, A1:A4)"),
+            Value::Error(ErrorValue::Value)
+        );
+
+        assert_eq!(eval("=SUMIF(B1:B4, \"Fire\", C1:C1)"), number(39.0));
+        assert_eq!(eval("=SUMIF(C1:C4, \">40\")"), number(89.0));
+        assert_eq!(eval("=COUNTIF(A1:A5, \"char*\")"), number(2.0));
+        assert_eq!(eval("=COUNTIF(C1:C5, \"<>45\")"), number(4.0));
+        assert_eq!(eval("=COUNTIF(C1:C4, \"\")"), number(1.0));
+
+        // Whole columns only read the rows holding something
+        assert_eq!(eval("=COUNT(C1:C999999999)"), number(3.0));
+        assert_eq!(eval("=SUMIF(B1:B999999999, \"Fire\", C1)"), number(39.0));
+        assert_eq!(
+            eval("=XLOOKUP(\"Squirtle\", A1:A999999999, C1:C999999999)"),
+            number(44.0)
+        );
+        assert_eq!(eval("=COUNTIF(C1:C999999999, \"\")"), number(999999995.0));
+
+        assert!(matches!(eval("=TODAY()"), Value::Date(_)));
+        assert_eq!(eval("=TODAY()-TODAY()"), number(0.0));
+        assert_eq!(eval("=TODAY(1)"), Value::Error(ErrorValue::Value));
+        assert_eq!(eval("=LOOKUP(1)"), Value::Error(ErrorValue::Name));
+    }
+
+    #[test]
+    fn criteria() {
+        assert!(wildcard("c?ar*", "Charizard"));
+        assert!(wildcard("*mime", "Mr. Mime"));
+        assert!(!wildcard("char", "Charizard"));
+        assert!(wildcard("what~?", "what?"));
+        assert!(!wildcard("what~?", "whats"));
+
+        let criterion = Criterion::new(Value::Text(">=10".into())).unwrap();
+        assert!(criterion.matches(&Value::Number(10.0)));
+        assert!(!criterion.matches(&Value::Text("20".into())));
+        let criterion = Criterion::new(Value::Bool(true)).unwrap();
+        assert!(criterion.matches(&Value::Bool(true)));
+        assert!(!criterion.matches(&Value::Number(1.0)));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod eval;
+pub mod functions;
+pub mod parser;
+pub mod reference;
+pub mod sheet;
@@ -0,0 +1,303 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, char, digit1, multispace0, one_of},
+    combinator::{all_consuming, map, map_opt, opt, recognize},
+    multi::{many0, many1, separated_list0},
+    number::complete::recognize_float,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+
+use super::reference::{CellRange, CellRef};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    /// `&`, joins the text of both sides.
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl BinaryOp {
+    fn parse(symbol: &str) -> Option<Self> {
+        match symbol {
+            "+" => Some(Self::Add),
+            "-" => Some(Self::Sub),
+            "*" => Some(Self::Mul),
+            "/" => Some(Self::Div),
+            "^" => Some(Self::Pow),
+            "&" => Some(Self::Concat),
+            "=" => Some(Self::Eq),
+            "<>" => Some(Self::Ne),
+            "<" => Some(Self::Lt),
+            "<=" => Some(Self::Le),
+            ">" => Some(Self::Gt),
+            ">=" => Some(Self::Ge),
+            _ => None,
+        }
+    }
+}
+
+/// Content of a cell after the `=`, references are resolved against the cell holding it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Formula {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Cell(CellRef),
+    Range(CellRange),
+    Neg(Box<Formula>),
+    Binary(BinaryOp, Box<Formula>, Box<Formula>),
+    /// Upper case function name and its arguments.
+    Call(String, Vec<Formula>),
+}
+
+impl Formula {
+    /// Every cell and range read by the formula.
+    pub fn references(&self) -> Vec<CellRange> {
+        let mut references = Vec::new();
+        self.collect_references(&mut references);
+        references
+    }
+
+    fn collect_references(&self, references: &mut Vec<CellRange>) {
+        match self {
+            Formula::Cell(cell) => references.push(CellRange::new(*cell, *cell)),
+            Formula::Range(range) => references.push(*range),
+            Formula::Neg(inner) => inner.collect_references(references),
+            Formula::Binary(_, left, right) => {
+                left.collect_references(references);
+                right.collect_references(references);
+            }
+            Formula::Call(_, args) => args
+                .iter()
+                .for_each(|arg| arg.collect_references(references)),
+            Formula::Number(_) | Formula::Text(_) | Formula::Bool(_) => (),
+        }
+    }
+}
+
+/// `=SUM(B2:B10)*C1`, the text of a formula cell at `origin`.
+pub fn parse_formula(text: &str, origin: CellRef) -> Option<Formula> {
+    let parser = FormulaParser { origin };
+    let body = text.trim_start().strip_prefix('=')?;
+    let (_, formula) =
+        all_consuming(terminated(|i| parser.comparison(i), multispace0))(body).ok()?;
+    Some(formula)
+}
+
+struct FormulaParser {
+    origin: CellRef,
+}
+
+impl FormulaParser {
+    /// Operands joined by operators of the same precedence, from left to right.
+    fn binary<'a>(
+        &self,
+        input: &'a str,
+        operand: fn(&Self, &'a str) -> IResult<&'a str, Formula>,
+        operators: fn(&'a str) -> IResult<&'a str, &'a str>,
+    ) -> IResult<&'a str, Formula> {
+        let (input, first) = operand(self, input)?;
+        let operator = map_opt(preceded(multispace0, operators), BinaryOp::parse);
+        let (input, rest) = many0(pair(operator, |i| operand(self, i)))(input)?;
+        let formula = rest.into_iter().fold(first, |left, (op, right)| {
+            Formula::Binary(op, Box::new(left), Box::new(right))
+        });
+        Ok((input, formula))
+    }
+
+    fn comparison<'a>(&self, input: &'a str) -> IResult<&'a str, Formula> {
+        self.binary(input, Self::concat, |i| {
+            alt((
+                tag("<>"),
+                tag("<="),
+                tag(">="),
+                tag("="),
+                tag("<"),
+                tag(">"),
+            ))(i)
+        })
+    }
+
+    fn concat<'a>(&self, input: &'a str) -> IResult<&'a str, Formula> {
+        self.binary(input, Self::sum, |i| tag("&")(i))
+    }
+
+    fn sum<'a>(&self, input: &'a str) -> IResult<&'a str, Formula> {
+        self.binary(input, Self::product, |i| recognize(one_of("+-"))(i))
+    }
+
+    fn product<'a>(&self, input: &'a str) -> IResult<&'a str, Formula> {
+        self.binary(input, Self::power, |i| recognize(one_of("*/"))(i))
+    }
+
+    fn power<'a>(&self, input: &'a str) -> IResult<&'a str, Formula> {
+        self.binary(input, Self::factor, |i| tag("^")(i))
+    }
+
+    /// Negation binds tighter than powers, `-2^2` is 4.
+    fn factor<'a>(&self, input: &'a str) -> IResult<&'a str, Formula> {
+        let input = input.trim_start();
+        alt((
+            map(preceded(char('-'), |i| self.factor(i)), |formula| {
+                Formula::Neg(Box::new(formula))
+            }),
+            preceded(char('+'), |i| self.factor(i)),
+            delimited(
+                char('('),
+                |i| self.comparison(i),
+                preceded(multispace0, char(')')),
+            ),
+            map(text, Formula::Text),
+            map_opt(recognize_float, |number: &str| {
+                number.parse().ok().map(Formula::Number)
+            }),
+            |i| self.name(i),
+        ))(input)
+    }
+
+    /// A function call, a boolean or a reference.
+    fn name<'a>(&self, input: &'a str) -> IResult<&'a str, Formula> {
+        let (rest, name) = identifier(input)?;
+        if let Ok((rest, args)) = self.arguments(rest) {
+            return Ok((rest, Formula::Call(name.to_ascii_uppercase(), args)));
+        }
+        if name.eq_ignore_ascii_case("TRUE") || name.eq_ignore_ascii_case("FALSE") {
+            return Ok((rest, Formula::Bool(name.eq_ignore_ascii_case("TRUE"))));
+        }
+
+        let invalid =
+            || nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify));
+        let start = CellRef::parse(name, self.origin).ok_or_else(invalid)?;
+        match preceded(char(':'), identifier)(rest) {
+            Ok((rest, end)) => {
+                let end = CellRef::parse(end, self.origin).ok_or_else(invalid)?;
+                Ok((rest, Formula::Range(CellRange::new(start, end))))
+            }
+            Err(_) => Ok((rest, Formula::Cell(start))),
+        }
+    }
+
+    fn arguments<'a>(&self, input: &'a str) -> IResult<&'a str, Vec<Formula>> {
+        delimited(
+            preceded(multispace0, char('(')),
+            separated_list0(preceded(multispace0, char(',')), |i| self.comparison(i)),
+            preceded(multispace0, char(')')),
+        )(input)
+    }
+}
+
+/// Function names and references, `SUM`, `$B$2`, `R[-1]C2`.
+fn identifier(input: &str) -> IResult<&str, &str> {
+    let offset = recognize(tuple((char('['), opt(char('-')), digit1, char(']'))));
+    recognize(many1(alt((
+        alphanumeric1,
+        tag("$"),
+        tag("_"),
+        tag("."),
+        offset,
+    ))))(input)
+}
+
+/// `"text"`, a doubled quote stands for a quote.
+fn text(input: &str) -> IResult<&str, String> {
+    let (input, _) = char('"')(input)?;
+    let mut value = String::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' if matches!(chars.peek(), Some((_, '"'))) => {
+                value.push('"');
+                chars.next();
+            }
+            '"' => return Ok((&input[i + 1..], value)),
+            c => value.push(c),
+        }
+    }
+    Err(nom::Err::Error(nom::error::Error::new(
+        input,
+        nom::error::ErrorKind::Char,
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_formula, BinaryOp, Formula};
+    use crate::formula::reference::{CellRange, CellRef};
+
+    #[test]
+    fn formulas() {
+        let origin = CellRef::new(0, 3);
+        let cell = |row, column| Formula::Cell(CellRef::new(row, column));
+        let binary = |op, left, right| Formula::Binary(op, Box::new(left), Box::new(right));
+
+        let sum = Formula::Call(
+            "SUM".into(),
+            vec![Formula::Range(CellRange::new(
+                CellRef::new(1, 1),
+                CellRef::new(9, 1),
+            ))],
+        );
+        assert_eq!(
+            parse_formula("=SUM(B2:B10)*C1", origin),
+            Some(binary(BinaryOp::Mul, sum, cell(0, 2)))
+        );
+        assert_eq!(
+            parse_formula("= 1 + R1C1 * -2 ^ 2", origin),
+            Some(binary(
+                BinaryOp::Add,
+                Formula::Number(1.0),
+                binary(
+                    BinaryOp::Mul,
+                    cell(0, 0),
+                    binary(
+                        BinaryOp::Pow,
+                        Formula::Neg(Box::new(Formula::Number(2.0))),
+                        Formula::Number(2.0)
+                    )
+                )
+            ))
+        );
+        assert_eq!(
+            parse_formula("=if(RC[-1]>=2, \"say \"\"hi\"\"\", true)", origin),
+            Some(Formula::Call(
+                "IF".into(),
+                vec![
+                    binary(BinaryOp::Ge, cell(0, 2), Formula::Number(2.0)),
+                    Formula::Text("say \"hi\"".into()),
+                    Formula::Bool(true)
+                ]
+            ))
+        );
+        assert_eq!(
+            parse_formula("=A1&\"x\"<>B1", origin),
+            Some(binary(
+                BinaryOp::Ne,
+                binary(BinaryOp::Concat, cell(0, 0), Formula::Text("x".into())),
+                cell(0, 1)
+            ))
+        );
+        assert_eq!(
+            parse_formula("=(A1+B1)*(A2", origin),
+            None,
+            "unbalanced parentheses"
+        );
+        assert_eq!(parse_formula("A1+B1", origin), None);
+        assert_eq!(parse_formula("=A0", origin), None);
+        assert_eq!(parse_formula("=\"open", origin), None);
+
+        let formula = parse_formula("=A1+SUM(B1:C2, 3)", origin).unwrap();
+        assert_eq!(formula.references().len(), 2);
+    }
+}
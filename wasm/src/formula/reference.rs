@@ -0,0 +1,143 @@
+use core::{fmt, ops::Range};
+
+use crate::utils::{header_name, header_position};
+
+/// Position of a cell, both zero based. Columns are named like generated headers and
+/// rows count the data rows in the order they were loaded, `A1` is the first value of the first column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellRef {
+    pub(crate) row: usize,
+    pub(crate) column: usize,
+}
+
+impl CellRef {
+    pub fn new(row: usize, column: usize) -> Self {
+        Self { row, column }
+    }
+
+    /// `B2`, `$B$2` or `R2C2`, `R[-1]C` is relative to `origin`. A1 is tried first.
+    pub fn parse(text: &str, origin: CellRef) -> Option<Self> {
+        let text = text.to_ascii_uppercase();
+        Self::parse_a1(&text).or_else(|| Self::parse_r1c1(&text, origin))
+    }
+
+    fn parse_a1(text: &str) -> Option<Self> {
+        let text = text.strip_prefix('$').unwrap_or(text);
+        let letters = text.find(|c: char| !c.is_ascii_uppercase())?;
+        let (name, row) = text.split_at(letters);
+        let row = row.strip_prefix('$').unwrap_or(row);
+        if !row.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let row = row.parse::<usize>().ok()?.checked_sub(1)?;
+        Some(Self::new(row, header_position(name.as_bytes())?))
+    }
+
+    fn parse_r1c1(text: &str, origin: CellRef) -> Option<Self> {
+        let (row, rest) = r1c1_part(text.strip_prefix('R')?, origin.row)?;
+        let (column, rest) = r1c1_part(rest.strip_prefix('C')?, origin.column)?;
+        rest.is_empty().then_some(Self::new(row, column))
+    }
+}
+
+/// `5` is one based, `[-1]` an offset from the origin and nothing the origin itself.
+fn r1c1_part(text: &str, origin: usize) -> Option<(usize, &str)> {
+    if let Some(rest) = text.strip_prefix('[') {
+        let (offset, rest) = rest.split_once(']')?;
+        return Some((origin.checked_add_signed(offset.parse().ok()?)?, rest));
+    }
+    let end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    match &text[..end] {
+        "" => Some((origin, text)),
+        digits => Some((digits.parse::<usize>().ok()?.checked_sub(1)?, &text[end..])),
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match header_name(self.column) {
+            Some(name) => write!(f, "{}{}", name, self.row + 1),
+            None => write!(f, "R{}C{}", self.row + 1, self.column + 1),
+        }
+    }
+}
+
+/// Rectangle of cells, both corners included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CellRange {
+    pub(crate) start: CellRef,
+    pub(crate) end: CellRef,
+}
+
+impl CellRange {
+    /// Corners in any order, the range is kept from the top left to the bottom right.
+    pub fn new(a: CellRef, b: CellRef) -> Self {
+        Self {
+            start: CellRef::new(a.row.min(b.row), a.column.min(b.column)),
+            end: CellRef::new(a.row.max(b.row), a.column.max(b.column)),
+        }
+    }
+
+    /// `B2:B10`, `R2C2:R[3]C`, a single cell is a range of one.
+    pub fn parse(text: &str, origin: CellRef) -> Option<Self> {
+        match text.split_once(':') {
+            Some((a, b)) => Some(Self::new(
+                CellRef::parse(a.trim(), origin)?,
+                CellRef::parse(b.trim(), origin)?,
+            )),
+            None => CellRef::parse(text.trim(), origin).map(|cell| Self::new(cell, cell)),
+        }
+    }
+
+    pub fn contains(&self, cell: CellRef) -> bool {
+        (self.start.row..=self.end.row).contains(&cell.row)
+            && (self.start.column..=self.end.column).contains(&cell.column)
+    }
+
+    /// Whether the range has cells in any of the rows, whatever the column.
+    pub fn overlaps_rows(&self, rows: &Range<usize>) -> bool {
+        self.start.row < rows.end && rows.start <= self.end.row
+    }
+
+    pub fn height(&self) -> usize {
+        self.end.row - self.start.row + 1
+    }
+
+    pub fn width(&self) -> usize {
+        self.end.column - self.start.column + 1
+    }
+}
+
+impl fmt::Display for CellRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CellRange, CellRef};
+
+    #[test]
+    fn references() {
+        let origin = CellRef::new(4, 2);
+        assert_eq!(CellRef::parse("B2", origin), Some(CellRef::new(1, 1)));
+        assert_eq!(CellRef::parse("$aa$10", origin), Some(CellRef::new(9, 26)));
+        assert_eq!(CellRef::parse("R2C3", origin), Some(CellRef::new(1, 2)));
+        assert_eq!(CellRef::parse("R[-1]C", origin), Some(CellRef::new(3, 2)));
+        assert_eq!(CellRef::parse("RC[2]", origin), Some(CellRef::new(4, 4)));
+        assert_eq!(CellRef::parse("R[-5]C", origin), None);
+        assert_eq!(CellRef::parse("B0", origin), None);
+        assert_eq!(CellRef::parse("B", origin), None);
+        assert_eq!(CellRef::parse("2B", origin), None);
+        assert_eq!(CellRef::new(9, 26).to_string(), "AA10");
+
+        let range = CellRange::parse("C10:b2", origin).unwrap();
+        assert_eq!(range.to_string(), "B2:C10");
+        assert_eq!((range.height(), range.width()), (9, 2));
+        assert!(range.contains(CellRef::new(5, 2)));
+        assert!(!range.contains(CellRef::new(10, 1)));
+    }
+}
@@ -0,0 +1,238 @@
+use core::ops::Range;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{
+    eval::{Cells, ErrorValue, Value},
+    parser::{parse_formula, Formula},
+    reference::{CellRange, CellRef},
+};
+use crate::{column::Column, slice::ColumnSlice};
+
+/// A formula as written, along with the cells it reads.
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    text: String,
+    formula: Formula,
+    references: Vec<CellRange>,
+}
+
+impl Entry {
+    fn reads(&self, cell: CellRef) -> bool {
+        self.references.iter().any(|range| range.contains(cell))
+    }
+
+    fn reads_rows(&self, rows: &Range<usize>) -> bool {
+        self.references
+            .iter()
+            .any(|range| range.overlaps_rows(rows))
+    }
+}
+
+/// Formula cells over the data of a frame. The ranges read by each formula make up the
+/// dependency graph, a change only recalculates the formulas reading the changed cell,
+/// directly or through other formulas.
+#[derive(Clone, Debug, Default)]
+pub struct Sheet {
+    formulas: HashMap<CellRef, Entry>,
+    values: HashMap<CellRef, Value>,
+}
+
+impl Sheet {
+    /// Replaces the content of the cell, `None` when the formula does not parse. Gives
+    /// back the recalculated cells.
+    pub fn set_formula(
+        &mut self,
+        cell: CellRef,
+        text: &str,
+        columns: &[Column],
+    ) -> Option<Vec<CellRef>> {
+        let formula = parse_formula(text, cell)?;
+        let entry = Entry {
+            text: text.trim().into(),
+            references: formula.references(),
+            formula,
+        };
+        self.formulas.insert(cell, entry);
+        Some(self.recalculate(self.dependents(cell), columns))
+    }
+
+    /// Gives the cell back to the data, the formulas reading it are recalculated.
+    pub fn clear_formula(&mut self, cell: CellRef, columns: &[Column]) -> Vec<CellRef> {
+        if self.formulas.remove(&cell).is_none() {
+            return Vec::new();
+        }
+        self.values.remove(&cell);
+        self.recalculate(self.dependents(cell), columns)
+    }
+
+    /// Recalculates every formula, for when the data itself changed.
+    pub fn recalculate_all(&mut self, columns: &[Column]) {
+        let cells = self.formulas.keys().copied().collect();
+        self.recalculate(cells, columns);
+    }
+
+    /// Recalculates the formulas reading any of the rows, for when they were appended,
+    /// along with the formulas reading those. Gives back the recalculated cells.
+    pub fn recalculate_rows(&mut self, rows: Range<usize>, columns: &[Column]) -> Vec<CellRef> {
+        let changed = self
+            .formulas
+            .iter()
+            .filter(|(_, entry)| entry.reads_rows(&rows))
+            .map(|(&cell, _)| cell);
+        let cells = self.readers(changed.collect());
+        self.recalculate(cells.into_iter().collect(), columns)
+    }
+
+    pub fn formula(&self, cell: CellRef) -> Option<&str> {
+        self.formulas.get(&cell).map(|entry| entry.text.as_str())
+    }
+
+    /// Value of any cell, formula or data.
+    pub fn value(&self, cell: CellRef, columns: &[Column]) -> Value {
+        Cells::new(columns, &self.values).value(cell)
+    }
+
+    /// Text of the formula cells among `rows` in place of the data of the column.
+    pub fn overlay(&self, column: usize, rows: &[usize], slice: ColumnSlice) -> ColumnSlice {
+        let computed: Vec<(usize, &Value)> = rows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &row)| Some((i, self.values.get(&CellRef::new(row, column))?)))
+            .collect();
+        if computed.is_empty() {
+            return slice;
+        }
+
+        let mut text = slice.text();
+        let mut slice = slice;
+        for (i, value) in computed {
+            text[i] = value.text();
+            slice.validity[i / 8] |= 1 << (i % 8);
+        }
+        slice.with_formatted(text)
+    }
+
+    /// Formula cells reading `cell`, directly or not, along with the cell when it holds a formula.
+    fn dependents(&self, cell: CellRef) -> Vec<CellRef> {
+        self.readers(vec![cell]).into_iter().collect()
+    }
+
+    /// Formula cells reading any of the cells, directly or not, along with the formula
+    /// cells among them.
+    fn readers(&self, cells: Vec<CellRef>) -> HashSet<CellRef> {
+        let mut found: HashSet<CellRef> = cells
+            .iter()
+            .copied()
+            .filter(|cell| self.formulas.contains_key(cell))
+            .collect();
+        let mut queue = VecDeque::from(cells);
+        while let Some(changed) = queue.pop_front() {
+            for (&reader, entry) in &self.formulas {
+                if entry.reads(changed) && found.insert(reader) {
+                    queue.push_back(reader);
+                }
+            }
+        }
+        found
+    }
+
+    /// Evaluates each cell once the formula cells it reads among them are, in the
+    /// order of a topological sort. Cells left over are part of a cycle or read one.
+    fn recalculate(&mut self, mut cells: Vec<CellRef>, columns: &[Column]) -> Vec<CellRef> {
+        cells.sort_unstable();
+
+        let mut readers: HashMap<CellRef, Vec<CellRef>> = HashMap::new();
+        let mut pending: HashMap<CellRef, usize> = HashMap::new();
+        for &cell in &cells {
+            let entry = &self.formulas[&cell];
+            let read: Vec<CellRef> = cells
+                .iter()
+                .copied()
+                .filter(|&other| entry.reads(other))
+                .collect();
+            pending.insert(cell, read.len());
+            read.into_iter()
+                .for_each(|other| readers.entry(other).or_default().push(cell));
+        }
+
+        let mut ready: VecDeque<CellRef> = cells
+            .iter()
+            .copied()
+            .filter(|cell| pending[cell] == 0)
+            .collect();
+        while let Some(cell) = ready.pop_front() {
+            let formula = &self.formulas[&cell].formula;
+            let value = Cells::new(columns, &self.values).evaluate(formula);
+            self.values.insert(cell, value);
+            for reader in readers.get(&cell).into_iter().flatten() {
+                let count = pending.get_mut(reader).expect("Readers are pending");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push_back(*reader);
+                }
+            }
+        }
+
+        for (cell, count) in pending {
+            if count > 0 {
+                self.values.insert(cell, Value::Error(ErrorValue::Circular));
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Sheet;
+    use crate::formula::{
+        eval::{ErrorValue, Value},
+        reference::CellRef,
+    };
+
+    #[test]
+    fn recalculate() {
+        let mut sheet = Sheet::default();
+        let cell = |text| CellRef::parse(text, CellRef::new(0, 0)).unwrap();
+        let set = |sheet: &mut Sheet, at, text| {
+            let mut changed = sheet.set_formula(cell(at), text, &[]).unwrap();
+            changed.sort_unstable();
+            changed
+        };
+
+        assert_eq!(set(&mut sheet, "A1", "=2"), [cell("A1")]);
+        assert_eq!(set(&mut sheet, "B1", "=A1*3"), [cell("B1")]);
+        assert_eq!(set(&mut sheet, "C1", "=SUM(A1:B1)"), [cell("C1")]);
+        assert_eq!(set(&mut sheet, "D1", "=A2"), [cell("D1")]);
+        assert_eq!(sheet.value(cell("C1"), &[]), Value::Number(8.0));
+
+        // Only the formulas reading A1 are recalculated, in dependency order
+        assert_eq!(
+            set(&mut sheet, "A1", "=10"),
+            [cell("A1"), cell("B1"), cell("C1")]
+        );
+        assert_eq!(sheet.value(cell("C1"), &[]), Value::Number(40.0));
+
+        let circular = Value::Error(ErrorValue::Circular);
+        set(&mut sheet, "A1", "=C1");
+        assert_eq!(sheet.value(cell("A1"), &[]), circular);
+        assert_eq!(sheet.value(cell("C1"), &[]), circular);
+        assert_eq!(sheet.value(cell("D1"), &[]), Value::Empty);
+        set(&mut sheet, "D1", "=D1+1");
+        assert_eq!(sheet.value(cell("D1"), &[]), circular);
+
+        assert_eq!(sheet.clear_formula(cell("A1"), &[]).len(), 2);
+        assert_eq!(sheet.value(cell("B1"), &[]), Value::Number(0.0));
+        assert_eq!(sheet.value(cell("A1"), &[]), Value::Empty);
+        assert_eq!(sheet.formula(cell("C1")), Some("=SUM(A1:B1)"));
+        assert!(sheet.set_formula(cell("A1"), "=1+", &[]).is_none());
+
+        // Appended rows only recalculate the formulas reading them
+        set(&mut sheet, "E1", "=A5");
+        set(&mut sheet, "F1", "=E1+1");
+        assert!(sheet.recalculate_rows(2..4, &[]).is_empty());
+        let mut changed = sheet.recalculate_rows(4..6, &[]);
+        changed.sort_unstable();
+        assert_eq!(changed, [cell("E1"), cell("F1")]);
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::{
+    aggregate::{scalar_column, Aggregate, Scalar},
+    column::Column,
+    diagnostics::SchemaError,
+    Words,
+};
+
+/// One output column of a group by, `count(*)` counts the rows of each group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupAggregate {
+    pub(crate) aggregate: Aggregate,
+    pub(crate) column: String,
+}
+
+impl GroupAggregate {
+    pub fn new(aggregate: Aggregate, column: &str) -> Self {
+        Self {
+            aggregate,
+            column: column.into(),
+        }
+    }
+
+    /// `avg(Attack)`, only `count` takes `*`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (function, column) = text.trim().strip_suffix(')')?.split_once('(')?;
+        let aggregate = Aggregate::from_function(function.trim())?;
+        let column = column.trim();
+        let valid = match column {
+            "" => false,
+            "*" => aggregate == Aggregate::Count,
+            _ => true,
+        };
+        valid.then(|| Self::new(aggregate, column))
+    }
+
+    pub fn name(&self) -> String {
+        format!("{}({})", self.aggregate.function(), self.column)
+    }
+}
+
+/// The key columns holding the first row of each group, followed by one column per aggregate.
+/// Groups keep the order in which their first row comes among `rows`.
+pub fn group_columns(
+    columns: &[Column],
+    rows: &[usize],
+    keys: &[String],
+    aggregates: &[GroupAggregate],
+) -> Result<Vec<Column>, SchemaError> {
+    let find = |name: &str| {
+        columns
+            .iter()
+            .find(|column| column.name() == name)
+            .ok_or_else(|| SchemaError::UnknownColumn(name.into()))
+    };
+    let keys = keys
+        .iter()
+        .map(|key| find(key))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let words: Vec<Words> = keys.iter().map(|column| column.series().words()).collect();
+    let words: Vec<Vec<&[u8]>> = words
+        .iter()
+        .map(|words| words.into_iter().collect())
+        .collect();
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut ids: HashMap<Vec<&[u8]>, usize> = HashMap::new();
+    for &row in rows {
+        let key = words.iter().map(|words| words[row]).collect();
+        let id = *ids.entry(key).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[id].push(row);
+    }
+
+    let firsts: Vec<usize> = groups.iter().map(|group| group[0]).collect();
+    let mut output: Vec<Column> = keys.iter().map(|column| column.gather(&firsts)).collect();
+    for aggregate in aggregates {
+        let values = match aggregate.column.as_str() {
+            "*" => groups
+                .iter()
+                .map(|group| Scalar::Int(group.len() as i128))
+                .collect(),
+            name => {
+                let column = find(name)?;
+                groups
+                    .iter()
+                    .map(|group| column.aggregate(group, aggregate.aggregate))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| SchemaError::NotNumeric(name.into()))?
+            }
+        };
+        output.push(scalar_column(aggregate.name(), values));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::GroupAggregate;
+    use crate::aggregate::Aggregate;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            GroupAggregate::parse(" avg(Sp. Atk) "),
+            Some(GroupAggregate::new(Aggregate::Average, "Sp. Atk"))
+        );
+        assert_eq!(
+            GroupAggregate::parse("COUNT(*)"),
+            Some(GroupAggregate::new(Aggregate::Count, "*"))
+        );
+        assert_eq!(GroupAggregate::parse("max(*)"), None);
+        assert_eq!(GroupAggregate::parse("mode(HP)"), None);
+        assert_eq!(GroupAggregate::new(Aggregate::Max, "HP").name(), "max(HP)");
+    }
+}
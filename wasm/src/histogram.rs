@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::describe::quantile;
+
+/// How the range of a column is split into bins.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Binning {
+    /// Bins of equal width over the range of the values.
+    Count(usize),
+    /// Increasing edges, values outside of them are left out.
+    Edges(Vec<f64>),
+    /// `log2(n) + 1` bins.
+    Sturges,
+    /// Bins `2 * IQR / cbrt(n)` wide and no more than values, Sturges when the spread is zero.
+    FreedmanDiaconis,
+}
+
+impl Binning {
+    pub fn edges(edges: Vec<f64>) -> Option<Self> {
+        let increasing = edges.windows(2).all(|pair| pair[0] < pair[1]);
+        let finite = edges.iter().all(|edge| edge.is_finite());
+        (edges.len() > 1 && increasing && finite).then_some(Binning::Edges(edges))
+    }
+
+    pub fn strategy(name: &str) -> Option<Self> {
+        match name {
+            "sturges" => Some(Binning::Sturges),
+            "fd" => Some(Binning::FreedmanDiaconis),
+            _ => None,
+        }
+    }
+}
+
+/// Counts per bin, `edges` holds one more value than `counts`. Bins hold their lower
+/// edge, the last one its upper edge too.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Histogram {
+    pub(crate) edges: Vec<f64>,
+    pub(crate) counts: Vec<usize>,
+}
+
+/// Histogram of the finite values, no values and no bins give an empty histogram.
+pub fn histogram(mut values: Vec<f64>, binning: &Binning) -> Histogram {
+    values.retain(|value| value.is_finite());
+    values.sort_by(f64::total_cmp);
+
+    let edges = match binning {
+        Binning::Edges(edges) => edges.clone(),
+        _ if values.is_empty() => return Histogram::default(),
+        Binning::Count(0) => return Histogram::default(),
+        Binning::Count(bins) => even_edges(&values, *bins),
+        Binning::Sturges => even_edges(&values, sturges(values.len())),
+        Binning::FreedmanDiaconis => {
+            let iqr = quantile(&values, 0.75) - quantile(&values, 0.25);
+            let width = 2.0 * iqr / (values.len() as f64).cbrt();
+            let range = values[values.len() - 1] - values[0];
+            let bins = if width > 0.0 {
+                ((range / width).ceil() as usize).clamp(1, values.len())
+            } else {
+                sturges(values.len())
+            };
+            even_edges(&values, bins)
+        }
+    };
+
+    let mut counts = vec![0; edges.len().saturating_sub(1)];
+    let last = edges.len().saturating_sub(1);
+    for value in values {
+        let bin = match edges.partition_point(|edge| *edge <= value) {
+            0 => continue,
+            bin if bin <= last => bin - 1,
+            _ if value == edges[last] => last - 1,
+            _ => continue,
+        };
+        counts[bin] += 1;
+    }
+    Histogram { edges, counts }
+}
+
+fn sturges(n: usize) -> usize {
+    (n as f64).log2().ceil() as usize + 1
+}
+
+/// Edges of equal width over sorted values, a single value gets a range of one around it.
+fn even_edges(sorted: &[f64], bins: usize) -> Vec<f64> {
+    let (mut low, mut high) = (sorted[0], sorted[sorted.len() - 1]);
+    if low == high {
+        low -= 0.5;
+        high += 0.5;
+    }
+    let width = (high - low) / bins as f64;
+    (0..bins)
+        .map(|i| low + width * i as f64)
+        .chain(Some(high))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{histogram, Binning};
+
+    #[test]
+    fn bins() {
+        let values = vec![1.0, 2.0, 2.5, 4.0, 5.0, f64::NAN];
+        let hist = histogram(values.clone(), &Binning::Count(4));
+        assert_eq!(hist.edges, [1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(hist.counts, [1, 2, 0, 2]);
+
+        let edges = Binning::edges(vec![0.0, 2.0, 3.0]).unwrap();
+        let hist = histogram(values.clone(), &edges);
+        assert_eq!(hist.counts, [1, 2]);
+
+        let hist = histogram(values, &Binning::Sturges);
+        assert_eq!(hist.counts.len(), 4);
+        assert_eq!(hist.counts.iter().sum::<usize>(), 5);
+
+        let hist = histogram(vec![3.0, 3.0], &Binning::FreedmanDiaconis);
+        assert_eq!(hist.edges, [2.5, 3.0, 3.5]);
+        assert_eq!(hist.counts, [0, 2]);
+
+        assert_eq!(Binning::edges(vec![1.0, 1.0]), None);
+        assert_eq!(Binning::strategy("scott"), None);
+        assert!(histogram(Vec::new(), &Binning::Sturges).edges.is_empty());
+    }
+}
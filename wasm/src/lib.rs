@@ -1,17 +1,40 @@
-#![feature(iter_intersperse)]
 #![feature(option_get_or_insert_default)]
+pub mod aggregate;
 pub mod column;
 pub mod command;
+pub mod compute;
 pub mod csv_parser;
+pub mod describe;
+pub mod diagnostics;
 pub mod filter;
+pub mod formula;
+pub mod group;
+pub mod histogram;
+pub mod number_format;
 pub mod public;
 pub mod series;
+pub mod slice;
+pub mod sniffer;
+pub mod sort;
 pub mod type_parser;
 pub mod utils;
 
-use column::{Column, SeriesEnum};
+use bitvec::prelude::BitSlice;
+use column::{Column, Schema};
+use compute::{compute_column, Expr};
 use console_error_panic_hook::hook;
-use csv_parser::LineSplitter;
+use csv_parser::{Cell, CsvDialect, LineSplitter};
+use describe::{numbers, ColumnStats};
+use diagnostics::{
+    CastReport, Diagnostic, ParseReport, Promotion, RowAction, RowPolicy, SchemaError,
+};
+use formula::{eval::Value, reference::CellRef, sheet::Sheet};
+use group::{group_columns, GroupAggregate};
+use histogram::{histogram, Binning, Histogram};
+use number_format::NumberFormat;
+use slice::ColumnSlice;
+use sniffer::Sniffed;
+use sort::{merge_index, sorted_index, SortKey};
 use std::panic;
 use type_parser::*;
 use utils::{HeaderFillerGenerator, LendingIterator};
@@ -47,7 +70,7 @@ impl Words {
 
     pub fn pop_at_last_offset(&mut self) -> Vec<u8> {
         let l = self.offsets.len() - 1;
-        let second_to_last = self.offsets[l - 1];
+        let second_to_last = l.checked_sub(1).map_or(0, |i| self.offsets[i]);
         let _ = self.offsets.pop();
         self.buff.drain(second_to_last..).collect()
     }
@@ -102,6 +125,9 @@ pub struct ChunkFromJsBytes {
     buffers: Vec<Words>,
     remainder: Option<Vec<u8>>,
     header: Option<Words>,
+    tail: Vec<u8>,
+    diagnostics: Vec<Diagnostic>,
+    n_rows: usize,
 }
 
 impl ChunkFromJsBytes {
@@ -113,75 +139,93 @@ impl ChunkFromJsBytes {
             missing_bytes: None,
             skip_header: false,
             n_cols: 0,
+            dialect: CsvDialect::default(),
+            policy: RowPolicy::default(),
+            position: (0, 0),
+            nulls: NullTokens::default(),
         }
     }
 
-    fn generate_codes(&self) -> Vec<Codes> {
+    /// Infers the type of every column whose type is not already fixed.
+    fn generate_codes(&self, fixed: &[Option<Codes>], numbers: &NumberFormat) -> Vec<Codes> {
         panic::set_hook(Box::new(hook));
-        let infer_size: usize = (self.buffers[0].len() as f32 * 0.1) as usize;
+        let infer_size = (self.buffers.first().map_or(0, Words::len) as f32 * 0.1) as usize;
         let n_words = infer_size.max(1);
 
         self.buffers
             .iter()
-            .map(move |buffer| {
-                let code: Codes = buffer
+            .zip(fixed)
+            .map(move |(buffer, fixed)| {
+                if let Some(code) = fixed {
+                    return *code;
+                }
+
+                let code = buffer
                     .into_iter()
                     .take(n_words)
-                    .map(|bytes| {
-                        let word = std::str::from_utf8(bytes).expect("Invalid bytes");
-                        match first_phase(word) {
-                            StageOne::Int(text) => IntegerTypes::from(text).into(),
-                            StageOne::Float(text) => FloatTypes::from(text).into(),
-                            StageOne::Any(text) if text.is_empty() => Codes::Null,
-                            val @ StageOne::Boolean(_) | val @ StageOne::Any(_) => val.into(),
-                        }
-                    })
-                    .max()
-                    .unwrap();
-                code
+                    .map(|word| numbers.infer(word))
+                    .fold(Codes::Null, Codes::promote);
+
+                // An empty sample says nothing, look at the whole chunk instead
+                match code {
+                    Codes::Null => buffer
+                        .into_iter()
+                        .map(|word| numbers.infer(word))
+                        .fold(Codes::Null, Codes::promote),
+                    code => code,
+                }
             })
             .collect()
     }
 
-    fn iter_with_code(self) -> impl Iterator<Item = (Codes, Words)> {
-        let codes = self.generate_codes();
-        codes.into_iter().zip(self.buffers.into_iter())
-    }
-
+    /// Holds the last row back until the next chunk completes it. A chunk without rows,
+    /// only a header, has nothing to hold back.
     pub fn pull_last_line(mut self) -> Self {
         panic::set_hook(Box::new(hook));
-        let first_len = self.buffers[0].len();
-        let mut remainder: Vec<u8> = Vec::default();
-        self.buffers
-            .iter_mut()
-            .filter(|v| v.len() == first_len)
-            .enumerate()
-            .for_each(|(i, v)| {
-                if i > 0 {
-                    remainder.push(b',');
-                }
-
-                let word = v.pop_at_last_offset();
-                remainder.extend_from_slice(&word);
+        if self.buffers.first().is_some_and(|v| !v.is_empty()) {
+            self.buffers.iter_mut().for_each(|v| {
+                let _ = v.pop_at_last_offset();
             });
-        self.remainder = Some(remainder);
+            self.n_rows -= 1;
+            self.remainder = Some(std::mem::take(&mut self.tail));
+        }
         self
     }
 
-    fn single_line(bytes: &[u8], n_cols: usize) -> Self {
-        let words = csv_parser::FieldIter::from_bytes(bytes);
+    fn single_line(
+        bytes: &[u8],
+        n_cols: usize,
+        dialect: CsvDialect,
+        policy: RowPolicy,
+        nulls: &NullTokens,
+        (row, offset): (usize, usize),
+    ) -> Result<Self, Diagnostic> {
+        let line = LineSplitter::new(bytes, dialect).next().unwrap_or_default();
+        let fields: Vec<Cell> = csv_parser::FieldIter::new(line, dialect).collect();
         let mut buffers: Vec<Words> = (0..n_cols).map(|_| Words::default()).collect();
+        let mut diagnostics = Vec::new();
 
-        buffers
-            .iter_mut()
-            .zip(words)
-            .for_each(|(v, word)| v.extend(word));
+        let action = policy.resolve(n_cols, fields.len());
+        if fields.len() != n_cols {
+            let diagnostic = Diagnostic::new(row + 1, offset, n_cols, fields.len(), line);
+            if action == RowAction::Fail {
+                return Err(diagnostic);
+            }
+            diagnostics.push(diagnostic);
+        }
 
-        Self {
+        if action == RowAction::Keep {
+            write_row(&mut buffers, &fields, nulls);
+        }
+
+        Ok(Self {
             buffers,
             header: None,
             remainder: None,
-        }
+            tail: Vec::new(),
+            diagnostics,
+            n_rows: 1,
+        })
     }
 
     fn fill_header(&mut self) -> Words {
@@ -201,11 +245,27 @@ impl ChunkFromJsBytes {
     }
 }
 
+/// Writes one row across the column buffers, padding missing fields with
+/// empty words and dropping the extra ones. Null tokens are written as empty words.
+fn write_row(buffers: &mut [Words], fields: &[Cell], nulls: &NullTokens) {
+    buffers
+        .iter_mut()
+        .enumerate()
+        .for_each(|(j, v)| match fields.get(j) {
+            Some(field) if !nulls.is_null(field) => v.extend(field),
+            _ => v.extend(&[]),
+        });
+}
+
 struct ChunkBuilder {
     bytes: Vec<u8>,
     missing_bytes: Option<Vec<u8>>,
     skip_header: bool,
     n_cols: usize,
+    dialect: CsvDialect,
+    policy: RowPolicy,
+    position: (usize, usize),
+    nulls: NullTokens,
 }
 
 impl ChunkBuilder {
@@ -224,63 +284,101 @@ impl ChunkBuilder {
         self
     }
 
-    fn read(&mut self) -> ChunkFromJsBytes {
-        panic::set_hook(Box::new(hook));
+    fn with_dialect(&mut self, dialect: CsvDialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
 
-        let mut lines = LineSplitter::from_bytes(self.bytes.as_slice());
+    fn with_policy(&mut self, policy: RowPolicy) -> &mut Self {
+        self.policy = policy;
+        self
+    }
 
-        let header = if self.skip_header {
-            let line = lines.next().expect("Empty buffer");
-            let words = csv_parser::FieldIter::from_bytes(line);
-            let mut parsed = Words::default();
+    fn with_null_tokens(&mut self, nulls: NullTokens) -> &mut Self {
+        self.nulls = nulls;
+        self
+    }
 
-            words.for_each(|word| parsed.extend(word));
-            Some(parsed)
-        } else {
-            None
-        };
+    /// Rows already read and byte offset of the first byte of this chunk,
+    /// both relative to the start of the stream.
+    fn with_position(&mut self, rows: usize, offset: usize) -> &mut Self {
+        self.position = (rows, offset);
+        self
+    }
 
-        let mut first_line = lines.next();
-        let first_chunk = if let Some(ref mut v) = self.missing_bytes {
-            let words =
-                csv_parser::FieldIter::from_bytes(first_line.expect("Empty buffer")).count();
-            if words < self.n_cols {
-                v.extend_from_slice(first_line.take().expect("Empty buffer"));
-            }
-            &v[..]
-        } else {
-            first_line.take().expect("Empty buffer")
-        };
+    fn read(&mut self) -> Result<ChunkFromJsBytes, Diagnostic> {
+        panic::set_hook(Box::new(hook));
 
-        let first_chunk: Vec<&[u8]> = csv_parser::FieldIter::from_bytes(first_chunk).collect();
+        if let Some(mut missing) = self.missing_bytes.take() {
+            missing.extend_from_slice(&self.bytes);
+            self.bytes = missing;
+        }
 
-        let width = self.n_cols.max(first_chunk.len());
-        let mut buffers: Vec<Words> = (0..width).map(|_| Words::default()).collect();
+        let dialect = self.dialect;
+        let tail = csv_parser::last_line(self.bytes.as_slice(), dialect).to_vec();
+        let mut lines = LineSplitter::new(self.bytes.as_slice(), dialect);
+
+        // An empty chunk has neither a header nor rows
+        let header = self
+            .skip_header
+            .then(|| lines.next())
+            .flatten()
+            .map(|line| {
+                let words = csv_parser::FieldIter::new(line, dialect);
+                let mut parsed = Words::default();
+
+                words.for_each(|word| parsed.extend(&word));
+                parsed
+            });
 
-        buffers
-            .iter_mut()
-            .zip(first_chunk.into_iter())
-            .for_each(|(v, word)| v.extend(word));
+        let rows: Vec<&[u8]> = lines.by_ref().collect();
+        let width = match (self.n_cols, &header) {
+            (0, Some(header)) => header.len(),
+            (0, None) => rows
+                .first()
+                .map_or(0, |row| csv_parser::FieldIter::new(row, dialect).count()),
+            (n_cols, _) => n_cols,
+        };
 
-        if let Some(v) = first_line {
-            let words = csv_parser::FieldIter::from_bytes(v);
-            words.enumerate().for_each(|(j, word)| {
-                buffers[j].extend(word);
-            })
-        }
+        let mut buffers: Vec<Words> = (0..width).map(|_| Words::default()).collect();
+        let mut diagnostics = Vec::new();
+        let (n_rows, offset) = self.position;
+        let first_row = n_rows + usize::from(header.is_some()) + 1;
+
+        for (i, line) in rows.iter().enumerate() {
+            let fields: Vec<Cell> = csv_parser::FieldIter::new(line, dialect).collect();
+
+            // The last line may be cut by the chunk boundary, it is checked once complete
+            if fields.len() != width && i + 1 < rows.len() {
+                let diagnostic = Diagnostic::new(
+                    first_row + i,
+                    offset + lines.offset_of(line),
+                    width,
+                    fields.len(),
+                    line,
+                );
 
-        for line in lines {
-            let words = csv_parser::FieldIter::from_bytes(line);
-            words.enumerate().for_each(|(j, word)| {
-                buffers[j].extend(word);
-            })
+                match self.policy.resolve(width, fields.len()) {
+                    RowAction::Fail => return Err(diagnostic),
+                    RowAction::Skip => {
+                        diagnostics.push(diagnostic);
+                        continue;
+                    }
+                    RowAction::Keep => diagnostics.push(diagnostic),
+                }
+            }
+
+            write_row(&mut buffers, &fields, &self.nulls);
         }
 
-        ChunkFromJsBytes {
+        Ok(ChunkFromJsBytes {
             buffers,
             remainder: None,
+            n_rows: usize::from(header.is_some()) + rows.len(),
             header,
-        }
+            tail,
+            diagnostics,
+        })
     }
 }
 
@@ -290,6 +388,19 @@ pub struct Frame {
     columns: Vec<Column>,
     n_chunks: usize,
     remainder: Vec<u8>,
+    dialect: Option<CsvDialect>,
+    sniffed: Option<Sniffed>,
+    report: ParseReport,
+    n_bytes: usize,
+    schema: Schema,
+    nulls: NullTokens,
+    numbers: NumberFormat,
+    tolerance: f64,
+    sort: Vec<SortKey>,
+    /// Columns added from expressions, recomputed as chunks come in.
+    computed: Vec<(String, Expr)>,
+    /// Formula cells over the data, recalculated as chunks come in.
+    sheet: Sheet,
 }
 
 #[allow(clippy::new_without_default)]
@@ -300,85 +411,217 @@ impl Frame {
             columns: Vec::new(),
             n_chunks: 0,
             remainder: Vec::new(),
+            dialect: None,
+            sniffed: None,
+            report: ParseReport::default(),
+            n_bytes: 0,
+            schema: Schema::default(),
+            nulls: NullTokens::default(),
+            numbers: NumberFormat::default(),
+            tolerance: 0.0,
+            sort: Vec::new(),
+            computed: Vec::new(),
+            sheet: Sheet::default(),
         }
     }
 
     fn new_from_entry(&mut self, mut entry: ChunkFromJsBytes) {
         let header = entry.fill_header();
+        let names: Vec<String> = header
+            .into_iter()
+            .map(|name_bytes| String::from_utf8(name_bytes.to_vec()).unwrap())
+            .collect();
+        let fixed: Vec<Option<Codes>> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| self.schema.get(i, name))
+            .collect();
 
         self.columns = entry
-            .iter_with_code()
-            .zip(header.into_iter())
-            .map(|((code, words), name_bytes)| match code {
-                code @ Codes::Boolean => {
-                    let parsed = parse_bool(words);
-                    let series = SeriesEnum::Bool(Box::new(parsed));
-                    let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
-                }
-                code @ Codes::Int32 => {
-                    let parsed = parse_type::<i32>(words);
-                    let series = SeriesEnum::I32(Box::new(parsed));
-                    let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
-                }
-                code @ Codes::Int64 => {
-                    let parsed = parse_type::<i64>(words);
-                    let series = SeriesEnum::I64(Box::new(parsed));
-                    let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
-                }
-                code @ Codes::Int128 => {
-                    let parsed = parse_type::<i128>(words);
-                    let series = SeriesEnum::I128(Box::new(parsed));
-                    let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
-                }
-                code @ Codes::Float32 => {
-                    let parsed = parse_type::<f32>(words);
-                    let series = SeriesEnum::F32(Box::new(parsed));
-                    let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
-                }
-                code @ Codes::Float64 => {
-                    let parsed = parse_type::<f64>(words);
-                    let series = SeriesEnum::F64(Box::new(parsed));
-                    let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
-                }
-                code @ Codes::Any => {
-                    let parsed = parse_utf8(words);
-                    let series = SeriesEnum::Any(Box::new(parsed));
-                    let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
-                }
-                _ => unreachable!(),
-            })
+            .generate_codes(&fixed, &self.numbers)
+            .into_iter()
+            .zip(names)
+            .map(|(code, name)| Column::empty(name, code, self.schema.format(code)))
             .collect();
+        self.extend_from_buffers(entry.buffers);
+    }
 
-        if let Some(v) = self.columns.get(0) {
-            self.index = (0..v.len()).collect();
+    fn extend_from_buffers(&mut self, buffers: Vec<Words>) {
+        let chunk = self.n_chunks;
+        let mut from = self.columns.first().map_or(0, Column::len);
+        let width = self.data_width();
+
+        for (i, (col, buff)) in self.columns[..width].iter_mut().zip(buffers).enumerate() {
+            if self.schema.get(i, col.name()).is_some() {
+                col.extend_exact(buff, &self.numbers);
+            } else if let Some(previous) = col.extend_from_words(buff, &self.numbers) {
+                // A column without values gets its first type, nothing read before changes
+                if previous != Codes::Null {
+                    let promotion = Promotion::new(col.name(), previous, col.dtype(), chunk);
+                    self.report.promotions.push(promotion);
+                }
+                // Values read before compare and compute differently in the wider type
+                from = 0;
+            }
         }
+        self.recompute(from);
+        self.reindex(from);
     }
 
-    fn extend_from_buffers(&mut self, buffers: Vec<Words>) {
-        self.columns
-            .iter_mut()
-            .zip(buffers.into_iter())
-            .for_each(|(col, buff)| col.extend_from_words(buff));
+    /// Number of columns read from the data, computed columns come after them.
+    fn data_width(&self) -> usize {
+        self.columns.len() - self.computed.len()
+    }
+
+    /// Brings computed columns and formulas up to date with the rows from `from` on, the
+    /// rows before are left as they are unless `from` is zero. A computed column whose
+    /// source turned to text gets nulls, it stays as tall as the data.
+    fn recompute(&mut self, from: usize) {
+        let rows = from..self.columns.first().map_or(0, Column::len);
+        for (name, expr) in &self.computed {
+            let position = self.columns.iter().position(|col| col.name() == name);
+            let computed = compute_column(name, expr, &self.columns, rows.clone())
+                .unwrap_or_else(|_| Column::nulls(name.clone(), rows.len()));
+            match position {
+                Some(position) if from == 0 => self.columns[position] = computed,
+                Some(position) => self.columns[position].append(&computed),
+                None => (),
+            }
+        }
+        if from == 0 {
+            self.sheet.recalculate_all(&self.columns);
+        } else {
+            self.sheet.recalculate_rows(rows, &self.columns);
+        }
     }
 
-    pub fn append(&mut self, bytes: &[u8], skip_header: bool) {
+    /// Appends a column computed from the others, it keeps up with later chunks.
+    pub fn add_column(&mut self, name: &str, expr: Expr) -> Result<(), SchemaError> {
+        if self.columns.iter().any(|col| col.name() == name) {
+            return Err(SchemaError::DuplicateColumn(name.into()));
+        }
+        let height = self.columns.first().map_or(0, Column::len);
+        let column = compute_column(name, &expr, &self.columns, 0..height)?;
+        self.columns.push(column);
+        self.computed.push((name.into(), expr));
+        self.sheet.recalculate_all(&self.columns);
+        Ok(())
+    }
+
+    fn parse_cell(cell: &str) -> Result<CellRef, SchemaError> {
+        CellRef::parse(cell.trim(), CellRef::new(0, 0))
+            .ok_or_else(|| SchemaError::InvalidCell(cell.into()))
+    }
+
+    /// Puts a formula like `=SUM(B2:B10)*C1` in a cell, rows count from the first data row
+    /// in load order. Gives back the cells whose value was recalculated.
+    pub fn set_formula(&mut self, cell: &str, text: &str) -> Result<Vec<CellRef>, SchemaError> {
+        let cell = Self::parse_cell(cell)?;
+        self.sheet
+            .set_formula(cell, text, &self.columns)
+            .ok_or_else(|| SchemaError::InvalidFormula(text.into()))
+    }
+
+    pub fn clear_formula(&mut self, cell: &str) -> Result<Vec<CellRef>, SchemaError> {
+        let cell = Self::parse_cell(cell)?;
+        Ok(self.sheet.clear_formula(cell, &self.columns))
+    }
+
+    pub fn formula(&self, cell: &str) -> Result<Option<&str>, SchemaError> {
+        Ok(self.sheet.formula(Self::parse_cell(cell)?))
+    }
+
+    pub fn cell_value(&self, cell: &str) -> Result<Value, SchemaError> {
+        Ok(self.sheet.value(Self::parse_cell(cell)?, &self.columns))
+    }
+
+    /// Keeps the index over every row, the rows from `from` on are sorted in with the
+    /// others. The whole index is sorted again when `from` is zero.
+    fn reindex(&mut self, from: usize) {
+        if self.sort.is_empty() {
+            let height = self.columns.first().map_or(0, Column::len);
+            self.index.extend(self.index.len()..height);
+        } else if from == 0 {
+            self.index = sorted_index(&self.columns, &self.sort);
+        } else {
+            self.index = merge_index(&self.columns, &self.sort, &self.index, from);
+        }
+    }
+
+    /// Orders the rows by the keys, the data itself stays in place.
+    pub fn sort_by(&mut self, keys: Vec<SortKey>) -> Result<(), SchemaError> {
+        let unknown = keys
+            .iter()
+            .find(|key| self.columns.iter().all(|col| col.name() != key.column));
+        if let Some(key) = unknown {
+            return Err(SchemaError::UnknownColumn(key.column.clone()));
+        }
+        self.sort = keys;
+        self.index.clear();
+        self.reindex(0);
+        Ok(())
+    }
+
+    /// A new frame with one row per distinct combination of the keys, rows are read in index order.
+    pub fn group_by(
+        &self,
+        keys: &[String],
+        aggregates: &[GroupAggregate],
+    ) -> Result<Frame, SchemaError> {
+        let mut frame = Frame::new();
+        frame.columns = group_columns(&self.columns, &self.index, keys, aggregates)?;
+        frame.numbers = self.numbers;
+        frame.tolerance = self.tolerance;
+        frame.reindex(0);
+        Ok(frame)
+    }
+
+    pub fn clear_sort(&mut self) {
+        self.sort.clear();
+        self.index.clear();
+        self.reindex(0);
+    }
+
+    /// Rows in display order.
+    pub fn index(&self) -> &[usize] {
+        &self.index
+    }
+
+    pub fn append(&mut self, bytes: &[u8], skip_header: Option<bool>) -> Result<(), Diagnostic> {
         panic::set_hook(Box::new(hook));
 
+        // An empty chunk tells nothing, not even the dialect or where the header is
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        if self.n_chunks == 0 && self.dialect.is_none() {
+            let sniffed = sniffer::sniff(bytes, &self.nulls);
+            self.dialect = Some(sniffed.dialect);
+            self.sniffed = Some(sniffed);
+        }
+
+        let dialect = self.dialect();
+        let skip_header = self.n_chunks == 0
+            && skip_header.unwrap_or_else(|| match self.sniffed {
+                Some(sniffed) => sniffed.has_header,
+                None => sniffer::has_header(bytes, dialect, &self.nulls),
+            });
+
         let old_rem = (!self.remainder.is_empty()).then(|| self.remainder.to_owned());
-        let chunk = ChunkFromJsBytes::from_bytes(bytes)
+        let mut chunk = ChunkFromJsBytes::from_bytes(bytes)
             .with_missing_bytes(old_rem)
-            .with_header(skip_header && self.n_chunks == 0)
-            .with_column_number(self.columns.len())
-            .read()
+            .with_header(skip_header)
+            .with_column_number(self.data_width())
+            .with_dialect(dialect)
+            .with_policy(self.report.policy)
+            .with_null_tokens(self.nulls.clone())
+            .with_position(self.report.rows, self.n_bytes - self.remainder.len())
+            .read()?
             .pull_last_line();
 
+        self.n_bytes += bytes.len();
+        self.report.rows += chunk.n_rows;
+        self.report.diagnostics.append(&mut chunk.diagnostics);
         self.remainder = chunk.remainder.clone().unwrap_or_default();
         if self.columns.is_empty() {
             self.new_from_entry(chunk);
@@ -387,21 +630,190 @@ impl Frame {
         };
 
         self.n_chunks += 1;
+        Ok(())
     }
 
-    pub fn append_remainder(&mut self) {
-        let chunk = ChunkFromJsBytes::single_line(&self.remainder, self.columns.len());
+    /// Reads the row held back by the last chunk, once the stream is over.
+    pub fn append_remainder(&mut self) -> Result<(), Diagnostic> {
+        let remainder = std::mem::take(&mut self.remainder);
+        if LineSplitter::new(&remainder, self.dialect())
+            .next()
+            .is_none()
+        {
+            return Ok(());
+        }
+
+        let mut chunk = ChunkFromJsBytes::single_line(
+            &remainder,
+            self.data_width(),
+            self.dialect(),
+            self.report.policy,
+            &self.nulls,
+            (self.report.rows, self.n_bytes - remainder.len()),
+        )?;
+
+        self.report.rows += chunk.n_rows;
+        self.report.diagnostics.append(&mut chunk.diagnostics);
         self.extend_from_buffers(chunk.buffers);
+        Ok(())
+    }
+
+    /// Replaces the words read as nulls in the chunks to come.
+    pub fn set_null_tokens(&mut self, nulls: NullTokens) {
+        self.nulls = nulls;
+    }
+
+    pub fn null_tokens(&self) -> &NullTokens {
+        &self.nulls
+    }
+
+    /// Replaces the separators used to read numbers in the chunks to come.
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
+    }
+
+    /// Largest difference at which two floats still count as equal in filters.
+    pub fn set_float_tolerance(&mut self, tolerance: f64) {
+        self.tolerance = tolerance.abs();
+    }
+
+    pub fn float_tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    pub fn set_row_policy(&mut self, policy: RowPolicy) {
+        self.report.policy = policy;
+    }
+
+    pub fn report(&self) -> &ParseReport {
+        &self.report
+    }
+
+    /// Replaces the sniffed dialect, only before the first chunk.
+    pub fn set_dialect(&mut self, dialect: CsvDialect) -> Result<(), SchemaError> {
+        if self.n_chunks > 0 {
+            return Err(SchemaError::Loaded);
+        }
+        self.dialect = Some(dialect);
+        Ok(())
+    }
+
+    fn dialect(&self) -> CsvDialect {
+        self.dialect.unwrap_or_default()
+    }
+
+    pub fn set_column_type(&mut self, name: &str, dtype: Codes) -> Result<(), SchemaError> {
+        if self.n_chunks > 0 {
+            return Err(SchemaError::Loaded);
+        }
+        self.schema.set_by_name(name, dtype);
+        Ok(())
+    }
+
+    pub fn set_column_type_at(&mut self, index: usize, dtype: Codes) -> Result<(), SchemaError> {
+        if self.n_chunks > 0 {
+            return Err(SchemaError::Loaded);
+        }
+        self.schema.set_by_index(index, dtype);
+        Ok(())
+    }
+
+    /// Sets how temporal values of the given type are read and written, for the
+    /// columns created or cast from now on.
+    pub fn set_format(&mut self, dtype: Codes, format: &str) -> Result<(), SchemaError> {
+        match dtype {
+            Codes::Date | Codes::Time | Codes::Timestamp => {
+                self.schema.set_format(dtype, format);
+                Ok(())
+            }
+            _ => Err(SchemaError::Unformatted(dtype)),
+        }
+    }
+
+    /// Converts a loaded column, later chunks keep the new type. Computed columns and
+    /// formulas reading it follow the new values, a type computed columns cannot read
+    /// is refused.
+    pub fn cast_column(&mut self, name: &str, dtype: Codes) -> Result<CastReport, SchemaError> {
+        if self.computed.iter().any(|(computed, _)| computed == name) {
+            return Err(SchemaError::Computed(name.into()));
+        }
+        let position = self
+            .columns
+            .iter()
+            .position(|col| col.name() == name)
+            .ok_or_else(|| SchemaError::UnknownColumn(name.into()))?;
+
+        let format = self.schema.format(dtype);
+        let (cast, report) = self.columns[position].cast(dtype, format, &self.numbers);
+        let previous = std::mem::replace(&mut self.columns[position], cast);
+        // Reading no rows is enough to tell whether the types still add up
+        let computable = self
+            .computed
+            .iter()
+            .try_for_each(|(name, expr)| compute_column(name, expr, &self.columns, 0..0).map(drop));
+        if let Err(err) = computable {
+            self.columns[position] = previous;
+            return Err(err);
+        }
+        self.schema.set_by_name(name, dtype);
+        self.recompute(0);
+        self.reindex(0);
+        Ok(report)
+    }
+
+    pub fn column(&self, name: &str) -> Result<&Column, SchemaError> {
+        self.columns
+            .iter()
+            .find(|col| col.name() == name)
+            .ok_or_else(|| SchemaError::UnknownColumn(name.into()))
     }
 
     pub fn find_by_name(&self, name: &str) -> &Column {
         self.columns.iter().find(|&col| col.name() == name).unwrap()
     }
+
+    pub fn describe(&self) -> Vec<ColumnStats> {
+        self.columns.iter().map(Column::describe).collect()
+    }
+
+    /// Histogram of a numeric column, over the rows set in the mask when there is one.
+    pub fn histogram(
+        &self,
+        column: &str,
+        binning: &Binning,
+        mask: Option<&BitSlice>,
+    ) -> Result<Histogram, SchemaError> {
+        let column = self
+            .columns
+            .iter()
+            .find(|col| col.name() == column)
+            .ok_or_else(|| SchemaError::UnknownColumn(column.into()))?;
+        let values = numbers(column.series(), mask)
+            .ok_or_else(|| SchemaError::NotNumeric(column.name().into()))?;
+        Ok(histogram(values, binning))
+    }
+
+    pub fn slice_columns(&self, offset: usize, size: usize) -> Vec<ColumnSlice> {
+        let rows: Vec<usize> = self.index.iter().skip(offset).take(size).copied().collect();
+        self.slice_rows(&rows)
+    }
+
+    /// Every column at the given rows, formula cells show their value in place of the data.
+    pub(crate) fn slice_rows(&self, rows: &[usize]) -> Vec<ColumnSlice> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| self.sheet.overlay(i, rows, column.slice(rows)))
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        aggregate::Scalar, compute::Operator, formula::eval::ErrorValue, slice::SliceValues,
+    };
 
     #[test]
     fn parse_bytes() {
@@ -432,7 +844,11 @@ mod test {
             buffers,
             remainder,
             header,
-        } = ChunkFromJsBytes::from_bytes(bytes).read().pull_last_line();
+            ..
+        } = ChunkFromJsBytes::from_bytes(bytes)
+            .read()
+            .unwrap()
+            .pull_last_line();
 
         assert_eq!(header, None);
         assert_eq!(buffers.len(), 3);
@@ -442,14 +858,634 @@ mod test {
     #[test]
     fn frame() {
         let bytes = "FieldOne,FieldTwo,FieldThree\nFlareon,2.5,1\nVaporeon,1.2,2".as_bytes();
-        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes)
+            .with_header(true)
+            .read()
+            .unwrap();
         let mut frame = Frame::new();
 
         frame.new_from_entry(chunk);
         assert_eq!(frame.width(), 3);
         assert_eq!(frame.height(), 2);
 
-        frame.append_remainder();
+        let rest = "\nJolteon,3.1,3".as_bytes();
+        frame.remainder = rest.into();
+        frame.n_bytes = bytes.len() + rest.len();
+        frame.append_remainder().unwrap();
+        assert_eq!(frame.height(), 3);
+        frame.append_remainder().unwrap();
+        assert_eq!(frame.height(), 3);
+    }
+
+    #[test]
+    fn remainder() {
+        let mut frame = Frame::new();
+        frame
+            .append("Name,Attack\n".as_bytes(), Some(true))
+            .unwrap();
+        assert!(frame.remainder.is_empty());
+        frame
+            .append("Flareon,130\nJolteon,65\n".as_bytes(), None)
+            .unwrap();
+        frame.append_remainder().unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(frame.columns[0].name(), "Name");
+        assert_eq!(frame.columns[1].dtype(), Codes::Int32);
+        assert!(frame.report.promotions.is_empty());
+        assert_eq!(
+            frame.find_by_name("Name").text(0, 2),
+            ["Flareon", "Jolteon"]
+        );
+        assert_eq!((frame.height(), frame.report().rows), (2, 3));
+
+        let mut frame = Frame::new();
+        frame.append("Name,Attack".as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+        assert_eq!((frame.width(), frame.height()), (2, 0));
+    }
+
+    #[test]
+    fn empty_chunks() {
+        for header in [true, false] {
+            let chunk = ChunkFromJsBytes::from_bytes(b"")
+                .with_header(header)
+                .read()
+                .unwrap();
+            assert!(chunk.buffers.is_empty() && chunk.header.is_none());
+            assert_eq!(chunk.n_rows, 0);
+        }
+
+        let mut frame = Frame::new();
+        frame.append(b"", None).unwrap();
+        frame.append_remainder().unwrap();
+        assert_eq!((frame.width(), frame.height()), (0, 0));
+
+        frame
+            .append("Name,Attack\nFlareon,130\n".as_bytes(), None)
+            .unwrap();
+        frame.append(b"", None).unwrap();
+        frame.append_remainder().unwrap();
+        assert_eq!(frame.columns[1].name(), "Attack");
+        assert_eq!(frame.find_by_name("Name").text(0, 2), ["Flareon"]);
+    }
+
+    #[test]
+    fn frame_with_dialect() {
+        let dialect = CsvDialect::new(b';', b'"', None, csv_parser::LineTerminator::CrLf);
+        let mut frame = Frame::new();
+        frame.set_dialect(dialect).unwrap();
+
+        frame
+            .append("Name;Attack\r\nFlareon;130\r\nVapor".as_bytes(), Some(true))
+            .unwrap();
+        frame
+            .append("eon;65\r\nJolteon;65\r".as_bytes(), Some(true))
+            .unwrap();
+        frame.append("\nEspeon;65".as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(frame.height(), 4);
+        assert_eq!(frame.set_dialect(dialect), Err(SchemaError::Loaded));
+        assert_eq!(
+            frame.find_by_name("Name").text(1, 3),
+            ["Vaporeon", "Jolteon", "Espeon"]
+        );
+        assert_eq!(frame.find_by_name("Attack").dtype(), Codes::Int32);
+    }
+
+    #[test]
+    fn frame_with_sniffed_dialect() {
+        let mut frame = Frame::new();
+
+        frame
+            .append(
+                "Name|Attack\nFlareon|130\nVaporeon|65\nJolt".as_bytes(),
+                None,
+            )
+            .unwrap();
+        frame.append("eon|65\n".as_bytes(), None).unwrap();
+        frame.append_remainder().unwrap();
+
+        let sniffed = frame.sniffed.unwrap();
+        assert_eq!(sniffed.dialect.delimiter, b'|');
+        assert!(sniffed.has_header);
+        assert_eq!(frame.height(), 3);
+        assert_eq!(frame.find_by_name("Attack").dtype(), Codes::Int32);
+    }
+
+    #[test]
+    fn frame_with_quoted_newlines() {
+        let mut frame = Frame::new();
+
+        frame
+            .append(
+                "Name,Note\nJolteon,Thunder\nFlareon,\"Fire\nsto".as_bytes(),
+                Some(true),
+            )
+            .unwrap();
+        frame
+            .append(
+                "ne\"\nVaporeon,\"Says \"\"Vapor\"\"\"\n".as_bytes(),
+                Some(true),
+            )
+            .unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(frame.height(), 3);
+        assert_eq!(
+            frame.find_by_name("Note").text(1, 2),
+            ["Fire\nstone", "Says \"Vapor\""]
+        );
+    }
+
+    #[test]
+    fn malformed_rows() {
+        let bytes =
+            "Name,Type,Attack\nFlareon,Fire\nJolteon,Electric,65,Extra\nEspeon,Psychic,65\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let diagnostics = frame.report().diagnostics();
         assert_eq!(frame.height(), 3);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic::new(2, 17, 3, 2, "Flareon,Fire".as_bytes())
+        );
+        assert_eq!((diagnostics[1].row, diagnostics[1].offset), (3, 30));
+        assert_eq!(frame.report().rows, 4);
+
+        let mut frame = Frame::new();
+        frame.set_row_policy(RowPolicy::Truncate);
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+        assert_eq!(frame.height(), 2);
+
+        let mut frame = Frame::new();
+        frame.set_row_policy(RowPolicy::Error);
+        let err = frame.append(bytes.as_bytes(), Some(true)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Row 2 at byte 17: expected 3 fields, found 2"
+        );
+        assert_eq!(frame.height(), 0);
+    }
+
+    fn double(column: &str) -> Expr {
+        Expr::Binary(
+            Operator::Mul,
+            Box::new(Expr::Column(column.into())),
+            Box::new(Expr::Number(Scalar::Int(2))),
+        )
+    }
+
+    #[test]
+    fn computed_width() {
+        for policy in [RowPolicy::Truncate, RowPolicy::Error] {
+            let mut frame = Frame::new();
+            frame.set_row_policy(policy);
+            frame
+                .append("Name,HP\nFlareon,65\nJolteon,60\n".as_bytes(), Some(true))
+                .unwrap();
+            frame.add_column("Double", double("HP")).unwrap();
+
+            // Rows are as wide as the data, the computed column is not part of it
+            frame
+                .append("Espeon,70\nUmbreon,95\n".as_bytes(), None)
+                .unwrap();
+            frame.append_remainder().unwrap();
+            assert_eq!(frame.height(), 4);
+            assert!(frame.report().diagnostics().is_empty());
+            assert_eq!(frame.columns[2].text(0, 4), ["130", "120", "140", "190"]);
+        }
+    }
+
+    #[test]
+    fn computed_sources() {
+        let mut frame = Frame::new();
+        frame
+            .append("Name,HP\nFlareon,65\nJolteon,60\n".as_bytes(), Some(true))
+            .unwrap();
+        frame.add_column("Double", double("HP")).unwrap();
+        assert_eq!(
+            frame.cast_column("HP", Codes::Any).unwrap_err(),
+            SchemaError::NotNumeric("HP".into())
+        );
+        assert_eq!(frame.find_by_name("HP").dtype(), Codes::Int32);
+
+        // Text read later leaves the computed column without values, not without rows
+        frame
+            .append("Espeon,70\nUmbreon,N/A\n".as_bytes(), None)
+            .unwrap();
+        frame.append("Glaceon,65\n".as_bytes(), None).unwrap();
+        frame.append_remainder().unwrap();
+        let lengths: Vec<usize> = frame.columns.iter().map(Column::len).collect();
+        assert_eq!(lengths, [5, 5, 5]);
+        assert_eq!(frame.find_by_name("Double").null_count(), 5);
+        frame
+            .sort_by(vec![SortKey::new("Double", false, true)])
+            .unwrap();
+        assert_eq!(frame.height(), 5);
+    }
+
+    #[test]
+    fn promote_columns() {
+        let mut frame = Frame::new();
+        frame
+            .append(
+                "Name,Attack\nFlareon,130\nJolteon,65\n".as_bytes(),
+                Some(true),
+            )
+            .unwrap();
+        assert_eq!(frame.columns[1].dtype(), Codes::Int32);
+
+        frame
+            .append("Vaporeon,65.5\nEspeon,\n".as_bytes(), None)
+            .unwrap();
+        frame.append("Umbreon,N/A\n".as_bytes(), None).unwrap();
+        frame.append_remainder().unwrap();
+
+        let promotions = frame.report().promotions();
+        assert_eq!(frame.columns[1].dtype(), Codes::Any);
+        assert_eq!(frame.columns[1].len(), frame.height());
+        assert_eq!(
+            promotions,
+            [
+                Promotion::new("Attack", Codes::Int32, Codes::Float64, 1),
+                Promotion::new("Attack", Codes::Float64, Codes::Any, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn promote_codes() {
+        assert_eq!(Codes::Null.promote(Codes::Int64), Codes::Int64);
+        assert_eq!(Codes::Int32.promote(Codes::Int128), Codes::Int128);
+        assert_eq!(Codes::Float32.promote(Codes::Int32), Codes::Float64);
+        assert_eq!(Codes::Boolean.promote(Codes::Int32), Codes::Any);
+        assert_eq!(Codes::Float64.promote(Codes::Any), Codes::Any);
+    }
+
+    #[test]
+    fn schema_overrides() {
+        let bytes = "Zip,Id,Name\n02134,1,Flareon\n10001,2,Jolteon\n";
+
+        let mut frame = Frame::new();
+        frame.set_column_type("Zip", Codes::Any).unwrap();
+        frame.set_column_type_at(1, Codes::Int64).unwrap();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append("90210,x,Espeon\n".as_bytes(), None).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(frame.columns[0].dtype(), Codes::Any);
+        assert_eq!(frame.columns[0].first(), "02134".to_string());
+        assert_eq!(frame.columns[1].dtype(), Codes::Int64);
+        assert_eq!(frame.columns[1].text(2, 1), [""]);
+        assert!(frame.report().promotions().is_empty());
+        assert_eq!(
+            frame.set_column_type("Name", Codes::Any),
+            Err(SchemaError::Loaded)
+        );
+    }
+
+    #[test]
+    fn cast_column() {
+        let bytes = "Name,Attack\nFlareon,130\nJolteon,65.5\nEspeon,\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let report = frame.cast_column("Attack", Codes::Int32).unwrap();
+        assert_eq!(frame.columns[1].dtype(), Codes::Int32);
+        assert_eq!(frame.columns[1].len(), 3);
+        assert_eq!((report.from, report.to), (Codes::Float64, Codes::Int32));
+        assert_eq!(report.failures().len(), 1);
+        assert_eq!(report.failures()[0].row, 1);
+        assert_eq!(report.failures()[0].value, "65.5");
+
+        frame.append("Umbreon,60\n".as_bytes(), None).unwrap();
+        frame.append_remainder().unwrap();
+        assert_eq!(frame.columns[1].dtype(), Codes::Int32);
+        assert_eq!(frame.columns[1].text(3, 1), ["60"]);
+
+        // Computed columns follow the cast and keep the type of their expression
+        frame.add_column("Double", double("Attack")).unwrap();
+        frame.cast_column("Attack", Codes::Float64).unwrap();
+        assert_eq!(frame.columns[2].dtype(), Codes::Float64);
+        assert_eq!(frame.columns[2].text(0, 4), ["260", "", "", "120"]);
+        assert_eq!(
+            frame.cast_column("Double", Codes::Int32),
+            Err(SchemaError::Computed("Double".into()))
+        );
+        assert_eq!(
+            frame.cast_column("Defense", Codes::Int32),
+            Err(SchemaError::UnknownColumn("Defense".into()))
+        );
+        assert_eq!(
+            Codes::try_from("Decimal"),
+            Err(SchemaError::UnknownType("Decimal".into()))
+        );
+    }
+
+    #[test]
+    fn temporal_columns() {
+        let bytes = "Name,Born,Seen,At\nFlareon,2020-12-25,9:05,2020-01-02T10:00:00Z\nJolteon,1996-02-27,23:59:59.5,2020-01-02 10:00\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame
+            .append("Espeon,2000-01-01T08:30,,\n".as_bytes(), None)
+            .unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(frame.columns[1].dtype(), Codes::Timestamp);
+        assert_eq!(frame.columns[2].dtype(), Codes::Time);
+        assert_eq!(frame.columns[3].dtype(), Codes::Timestamp);
+        assert_eq!(
+            frame.columns[1].text(0, 3),
+            [
+                "2020-12-25T00:00:00",
+                "1996-02-27T00:00:00",
+                "2000-01-01T08:30:00"
+            ]
+        );
+        assert_eq!(frame.columns[2].text(0, 2), ["09:05:00", "23:59:59.500"]);
+        assert_eq!(
+            frame.columns[3].text(0, 2),
+            ["2020-01-02T10:00:00+00:00", "2020-01-02T10:00:00"]
+        );
+    }
+
+    #[test]
+    fn temporal_formats() {
+        let bytes = "Name,Born\nFlareon,25.12.2020\nJolteon,27.02.1996\n";
+
+        let mut frame = Frame::new();
+        frame.set_column_type("Born", Codes::Date).unwrap();
+        frame.set_format(Codes::Date, "%d.%m.%Y").unwrap();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(frame.columns[1].dtype(), Codes::Date);
+        assert_eq!(frame.columns[1].first(), "25.12.2020".to_string());
+        frame.set_formula("C1", "=B1-B2").unwrap();
+        assert_eq!(frame.cell_value("C1"), Ok(Value::Number(9068.0)));
+
+        frame.cast_column("Name", Codes::Date).unwrap();
+        let report = frame.cast_column("Born", Codes::Any).unwrap();
+        assert!(report.failures().is_empty());
+        assert_eq!(frame.columns[1].first(), "25.12.2020".to_string());
+        assert_eq!(frame.cell_value("C1"), Ok(Value::Error(ErrorValue::Value)));
+        assert_eq!(frame.columns[0].dtype(), Codes::Date);
+        assert_eq!(frame.columns[0].first(), "".to_string());
+        assert_eq!(
+            frame.set_format(Codes::Int32, "%d"),
+            Err(SchemaError::Unformatted(Codes::Int32))
+        );
+    }
+
+    #[test]
+    fn null_tokens() {
+        let bytes =
+            "Name,Attack,Type\nFlareon,NA,Fire\nJolteon,65,-\nVaporeon,#N/A,Water\nEspeon,,?\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let attack = frame.find_by_name("Attack");
+        assert_eq!(attack.dtype(), Codes::Int32);
+        assert_eq!((attack.null_count(), attack.valid_count()), (3, 1));
+        assert_eq!(
+            frame.column("Defense").err(),
+            Some(SchemaError::UnknownColumn("Defense".into()))
+        );
+        assert_eq!(frame.find_by_name("Type").null_count(), 1);
+
+        let mut frame = Frame::new();
+        frame.set_null_tokens(NullTokens::new(["?"]));
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        assert_eq!(frame.find_by_name("Attack").dtype(), Codes::Any);
+        assert_eq!(frame.find_by_name("Type").null_count(), 1);
+        assert_eq!(frame.find_by_name("Type").text(1, 1), ["-"]);
+    }
+
+    #[test]
+    fn number_formats() {
+        let bytes = "Name,Price,Share,Weight,Size,Count\nFlareon,\"$1,234.50\",45%,1e-5,1.,\"1,000\"\nJolteon,$12.00,12.6%,2.5E3,2,2.5\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let price = frame.find_by_name("Price");
+        assert_eq!(price.dtype(), Codes::Float32);
+        assert_eq!(price.series().f32().unwrap()[0], Some(1234.5));
+        assert_eq!(price.text(0, 2), ["$1,234.50", "$12.00"]);
+
+        let share = frame.find_by_name("Share");
+        assert!(share.dtype().is_numeric());
+        assert_eq!(share.text(0, 2), ["45%", "12.6%"]);
+        assert!(frame.find_by_name("Weight").dtype().is_numeric());
+        assert!(frame.find_by_name("Size").dtype().is_numeric());
+        assert_eq!(frame.find_by_name("Count").text(0, 2), ["1,000", "2.5"]);
+
+        frame.cast_column("Share", Codes::Any).unwrap();
+        assert_eq!(frame.find_by_name("Share").text(0, 2), ["45%", "12.6%"]);
+
+        let mut frame = Frame::new();
+        frame.set_number_format(NumberFormat::new(b',', Some(b'.')));
+        frame
+            .append(
+                "Name;Price\nFlareon;1.234,5\nJolteon;12,26\n".as_bytes(),
+                Some(true),
+            )
+            .unwrap();
+        frame.append_remainder().unwrap();
+
+        let price = frame.find_by_name("Price");
+        assert!(price.dtype().is_numeric());
+        assert_eq!(price.text(0, 2), ["1.234,5", "12,26"]);
+        assert_eq!(price.series().f32().unwrap()[1], Some(12.26));
+    }
+
+    #[test]
+    fn slice_columns() {
+        let bytes = "Name,Attack,Weight,Legendary
+Flareon,130,25.0,false
+DELIMITER_TOKEN,,24.5,true
+Espeon,65,26.5,
+";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let slices = frame.slice_columns(1, 5);
+        assert_eq!(slices.len(), 4);
+        assert_eq!(slices[0].text(), ["DELIMITER_TOKEN", "Espeon"]);
+
+        let attack = &slices[1];
+        assert_eq!(attack.dtype, Codes::Int32);
+        assert_eq!(attack.values, SliceValues::Int32(vec![0, 65]));
+        assert_eq!(attack.validity, vec![0b10]);
+        assert_eq!(attack.text(), ["", "65"]);
+
+        assert_eq!(slices[2].values, SliceValues::Float64(vec![24.5, 26.5]));
+        assert_eq!(slices[3].values, SliceValues::Boolean(vec![1, 0]));
+        assert!(!slices[3].is_valid(1));
+        assert!(frame.slice_columns(3, 2)[0].is_empty());
+    }
+
+    #[test]
+    fn sort_rows() {
+        let bytes = "Name,Type,Attack\nFlareon,Fire,130\nVaporeon,Water,65\nMoltres,Fire,\nSuicune,Water,75\nJolteon,Electric,65\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame
+            .sort_by(vec![
+                SortKey::new("Type", false, false),
+                SortKey::new("Attack", true, false),
+            ])
+            .unwrap();
+        assert_eq!(frame.index(), [0, 2, 3, 1]);
+        assert_eq!(frame.slice_columns(1, 2)[0].text(), ["Moltres", "Suicune"]);
+
+        frame
+            .sort_by(vec![SortKey::new("Attack", false, true)])
+            .unwrap();
+        assert_eq!(frame.index(), [2, 1, 3, 0]);
+
+        frame.append("Espeon,Psychic,\n".as_bytes(), None).unwrap();
+        frame.append_remainder().unwrap();
+        assert_eq!(frame.index(), [2, 5, 1, 4, 3, 0]);
+
+        assert_eq!(
+            frame.sort_by(vec![SortKey::new("Defense", false, false)]),
+            Err(SchemaError::UnknownColumn("Defense".into()))
+        );
+        frame.clear_sort();
+        assert_eq!(frame.index(), [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn describe() {
+        let bytes = "Name,Type,Attack,Found\nFlareon,Fire,130,2020-01-01\nVaporeon,Water,65,\nMoltres,Fire,,2020-01-01\nJolteon,Electric,65,2020-03-01\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+        let stats = frame.describe();
+
+        let attack = &stats[2];
+        assert_eq!(
+            (attack.count, attack.null_count, attack.distinct),
+            (3, 1, 2)
+        );
+        let numeric = attack.numeric.unwrap();
+        assert_eq!(
+            (numeric.min, numeric.q1, numeric.median),
+            (65.0, 65.0, 65.0)
+        );
+        assert_eq!((numeric.q3, numeric.max), (97.5, 130.0));
+        assert!((numeric.mean - 86.666).abs() < 1e-3);
+        assert!((numeric.std.unwrap() - 37.528).abs() < 1e-3);
+        assert!(attack.text.is_none());
+
+        let kind = stats[1].text.as_ref().unwrap();
+        assert_eq!(kind.top[0], ("Fire".to_string(), 2));
+        assert_eq!(kind.top[1], ("Electric".to_string(), 1));
+        assert_eq!((kind.min_length, kind.max_length), (4, 8));
+        assert_eq!(stats[1].distinct, 3);
+
+        assert_eq!(stats[3].dtype, Codes::Date);
+        assert_eq!((stats[3].count, stats[3].distinct), (3, 2));
+        assert!(stats[3].numeric.is_none());
+    }
+
+    #[test]
+    fn histogram() {
+        let bytes = "Name,Type,Attack\nFlareon,Fire,130\nVaporeon,Water,65\nMoltres,Fire,100\nJolteon,Electric,\nEevee,Normal,55\n";
+
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+        frame.append_remainder().unwrap();
+
+        let hist = frame.histogram("Attack", &Binning::Count(3), None).unwrap();
+        assert_eq!(hist.edges, [55.0, 80.0, 105.0, 130.0]);
+        assert_eq!(hist.counts, [2, 1, 1]);
+
+        let mut filter = crate::filter::Filter::default();
+        filter.add_equalto_filter(&frame, b"Fire", "Type").unwrap();
+        let hist = frame
+            .histogram("Attack", &Binning::Count(3), Some(filter.get()))
+            .unwrap();
+        assert_eq!(hist.edges, [100.0, 110.0, 120.0, 130.0]);
+        assert_eq!(hist.counts, [1, 0, 1]);
+
+        assert_eq!(
+            frame.histogram("Type", &Binning::Sturges, None),
+            Err(SchemaError::NotNumeric("Type".into()))
+        );
+    }
+
+    #[test]
+    fn formulas() {
+        let bytes = "Name,HP,Attack\nBulbasaur,45,49\nIvysaur,60,62\nVenusaur,80,82\n";
+        let mut frame = Frame::new();
+        frame.append(bytes.as_bytes(), Some(true)).unwrap();
+
+        // The last row only comes with the remainder
+        let changed = frame.set_formula("D1", "=SUM(B1:B3)*C1").unwrap();
+        assert_eq!(changed, [CellRef::new(0, 3)]);
+        frame.set_formula("A2", "=D1/R[-1]C[1]").unwrap();
+        assert_eq!(frame.cell_value("D1"), Ok(Value::Number(5145.0)));
+        assert_eq!(frame.cell_value("A3"), Ok(Value::Empty));
+
+        frame.append_remainder().unwrap();
+        assert_eq!(frame.cell_value("D1"), Ok(Value::Number(9065.0)));
+        assert_eq!(frame.cell_value("a2"), Ok(Value::Number(9065.0 / 45.0)));
+        assert_eq!(frame.cell_value("A3"), Ok(Value::Text("Venusaur".into())));
+
+        frame
+            .set_formula(
+                "E1",
+                "=VLOOKUP(\"venusaur\", A1:C3, 3, FALSE)+COUNTIF(B1:B3, \">50\")",
+            )
+            .unwrap();
+        assert_eq!(frame.cell_value("E1"), Ok(Value::Number(84.0)));
+
+        let slice = frame.slice_columns(0, 3);
+        assert_eq!(
+            slice[0].text(),
+            ["Bulbasaur", "201.444444444444", "Venusaur"]
+        );
+        let mut filter = crate::filter::Filter::default();
+        filter.add_equalto_filter(&frame, b"60", "HP").unwrap();
+        assert_eq!(filter.slice(&frame, 0, 10)[0].text(), ["201.444444444444"]);
+
+        frame.set_formula("B1", "=A2").unwrap();
+        assert_eq!(
+            frame.cell_value("D1"),
+            Ok(Value::Error(ErrorValue::Circular))
+        );
+        assert_eq!(frame.clear_formula("B1").unwrap().len(), 3);
+        assert_eq!(frame.formula("A2"), Ok(Some("=D1/R[-1]C[1]")));
+
+        assert_eq!(
+            frame.set_formula("1A", "=1"),
+            Err(SchemaError::InvalidCell("1A".into()))
+        );
+        assert_eq!(
+            frame.set_formula("A1", "=SUM(B1:B3"),
+            Err(SchemaError::InvalidFormula("=SUM(B1:B3".into()))
+        );
     }
 }
@@ -0,0 +1,259 @@
+use crate::{
+    type_parser::{infer_bytes, Codes},
+    Words,
+};
+
+const CURRENCIES: &[&str] = &["$", "€", "£", "¥"];
+
+/// Separators used to read numbers written for humans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub(crate) decimal: u8,
+    pub(crate) thousands: Option<u8>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal: b'.',
+            thousands: Some(b','),
+        }
+    }
+}
+
+/// How the numbers of a column were written, used to write them back the same way.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NumericDisplay {
+    prefix: String,
+    suffix: String,
+    percent: bool,
+    decimals: usize,
+    decimal: u8,
+    thousands: Option<u8>,
+}
+
+impl NumberFormat {
+    pub fn new(decimal: u8, thousands: Option<u8>) -> Self {
+        Self {
+            decimal,
+            thousands: thousands.filter(|&thousands| thousands != decimal),
+        }
+    }
+
+    /// Rewrites a formatted number as a plain one, along with the way it was written.
+    /// Words that are plain numbers already, or not numbers at all, give `None`.
+    pub fn normalize(&self, word: &[u8]) -> Option<(String, NumericDisplay)> {
+        let word = std::str::from_utf8(word).ok()?.trim();
+        let (negative, word) = match word.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, word.strip_prefix('+').unwrap_or(word)),
+        };
+
+        let mut display = NumericDisplay {
+            decimal: self.decimal,
+            ..NumericDisplay::default()
+        };
+
+        let word = match CURRENCIES.iter().find(|&c| word.starts_with(c)) {
+            Some(currency) => {
+                let rest = word[currency.len()..].trim_start();
+                display.prefix = word[..word.len() - rest.len()].into();
+                rest
+            }
+            None => word,
+        };
+        let word = if let Some(rest) = word.strip_suffix('%') {
+            display.percent = true;
+            display.suffix = "%".into();
+            rest.trim_end()
+        } else if let Some(currency) = CURRENCIES.iter().find(|&c| word.ends_with(c)) {
+            let rest = word[..word.len() - currency.len()].trim_end();
+            display.suffix = word[rest.len()..].into();
+            rest
+        } else {
+            word
+        };
+        let negative = negative || word.starts_with('-');
+        let word = word.strip_prefix('-').unwrap_or(word);
+
+        let (integer, fraction) = match word.split_once(char::from(self.decimal)) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (word, None),
+        };
+        let grouped = self.is_grouped(integer)?;
+        let digits: String = integer.chars().filter(|c| c.is_ascii_digit()).collect();
+        let fraction = fraction.unwrap_or_default();
+
+        if digits.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        if !grouped && !display.percent && display.prefix.is_empty() && display.suffix.is_empty() {
+            // Plain numbers need no rewriting, unless the decimal separator is not a dot
+            if self.decimal == b'.' || fraction.is_empty() {
+                return None;
+            }
+        }
+
+        display.decimals = fraction.len();
+        display.thousands = self.thousands.filter(|_| grouped);
+
+        let sign = if negative { "-" } else { "" };
+        let plain = match (display.percent, fraction) {
+            (true, fraction) => {
+                let value: f64 = format!("{}{}.{}0", sign, digits, fraction).parse().ok()?;
+                (value / 100.0).to_string()
+            }
+            (false, "") => format!("{}{}", sign, digits),
+            (false, fraction) => format!("{}{}.{}", sign, digits, fraction),
+        };
+
+        Some((plain, display))
+    }
+
+    /// Plain version of every word, with the way the first formatted one was written.
+    pub fn normalize_words(&self, words: &Words) -> (Words, Option<NumericDisplay>) {
+        let mut normalized = Words::default();
+        let mut display = None;
+
+        for word in words {
+            match self.normalize(word) {
+                Some((plain, found)) => {
+                    normalized.extend(plain.as_bytes());
+                    display.get_or_insert(found);
+                }
+                None => normalized.extend(word),
+            }
+        }
+
+        (normalized, display)
+    }
+
+    pub fn infer(&self, word: &[u8]) -> Codes {
+        match self.normalize(word) {
+            Some((plain, _)) => infer_bytes(plain.as_bytes()),
+            None => infer_bytes(word),
+        }
+    }
+
+    /// Whether the integer part uses thousands separators, `None` when it is misplaced.
+    fn is_grouped(&self, integer: &str) -> Option<bool> {
+        let separator = match self.thousands {
+            Some(separator) if integer.contains(char::from(separator)) => char::from(separator),
+            _ => return Some(false),
+        };
+
+        let mut groups = integer.split(separator);
+        let first = groups.next()?;
+        let valid = (1..=3).contains(&first.len())
+            && groups.all(|group| group.len() == 3 && group.chars().all(|c| c.is_ascii_digit()));
+
+        valid.then_some(true)
+    }
+}
+
+impl NumericDisplay {
+    /// Writes a plain number the way the column was written, with at least as many decimals.
+    /// Decimals of the value beyond those are kept, rounding would change the value.
+    pub fn render(&self, plain: &str) -> String {
+        let (sign, number) = match plain.strip_prefix('-') {
+            Some(number) => ("-", number),
+            None => ("", plain),
+        };
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        let digits = integer.chars().chain(fraction.chars());
+        if integer.is_empty() || !digits.clone().all(|c| c.is_ascii_digit()) {
+            return plain.into();
+        }
+
+        let (integer, mut fraction) = if self.percent {
+            // Moving the decimal point is exact where multiplying by 100 is not
+            let fraction = format!("{:0<2}", fraction);
+            let integer = format!("{}{}", integer, &fraction[..2]);
+            let integer = match integer.trim_start_matches('0') {
+                "" => "0".into(),
+                trimmed => trimmed.to_string(),
+            };
+            (integer, fraction[2..].trim_end_matches('0').to_string())
+        } else {
+            (integer.to_string(), fraction.to_string())
+        };
+        if fraction.len() < self.decimals {
+            fraction.push_str(&"0".repeat(self.decimals - fraction.len()));
+        }
+        let integer = integer.as_str();
+        let fraction = (!fraction.is_empty()).then_some(fraction.as_str());
+
+        let mut ret = format!("{}{}", sign, self.prefix);
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                if let Some(thousands) = self.thousands {
+                    ret.push(char::from(thousands));
+                }
+            }
+            ret.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            ret.push(char::from(self.decimal));
+            ret.push_str(fraction);
+        }
+        ret.push_str(&self.suffix);
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NumberFormat;
+
+    fn roundtrip(format: NumberFormat, word: &str) -> (String, String) {
+        let (plain, display) = format.normalize(word.as_bytes()).unwrap();
+        let rendered = display.render(&plain);
+        (plain, rendered)
+    }
+
+    #[test]
+    fn normalize() {
+        let format = NumberFormat::default();
+
+        assert_eq!(format.normalize(b"1234.5"), None);
+        assert_eq!(format.normalize(b"Flareon"), None);
+        assert_eq!(format.normalize(b"1,23"), None);
+        assert_eq!(
+            roundtrip(format, "1,234.50"),
+            ("1234.50".into(), "1,234.50".into())
+        );
+        assert_eq!(
+            roundtrip(format, "$12.00"),
+            ("12.00".into(), "$12.00".into())
+        );
+        assert_eq!(roundtrip(format, "-$5"), ("-5".into(), "-$5".into()));
+        assert_eq!(roundtrip(format, "45%"), ("0.45".into(), "45%".into()));
+        assert_eq!(format.normalize("12,5 €".as_bytes()), None);
+
+        let format = NumberFormat::new(b',', Some(b'.'));
+        assert_eq!(
+            roundtrip(format, "1.234,5"),
+            ("1234.5".into(), "1.234,5".into())
+        );
+        assert_eq!(
+            roundtrip(format, "12,5 €"),
+            ("12.5".into(), "12,5 €".into())
+        );
+        assert_eq!(format.normalize(b"1234"), None);
+    }
+
+    #[test]
+    fn render() {
+        let (_, display) = NumberFormat::default().normalize(b"$1,234.50").unwrap();
+
+        assert_eq!(display.render("1234567.5"), "$1,234,567.50");
+        assert_eq!(display.render("-12.345"), "-$12.345");
+        assert_eq!(display.render("3"), "$3.00");
+        assert_eq!(display.render(""), "");
+
+        let (_, display) = NumberFormat::default().normalize(b"45%").unwrap();
+        assert_eq!(display.render("0.126"), "12.6%");
+        assert_eq!(display.render("0.05"), "5%");
+        assert_eq!(display.render("-1.5"), "-150%");
+    }
+}
@@ -1,9 +1,22 @@
 use crate::{
-    command::exec::{exec, Slice},
+    column::Column,
+    command::{
+        exec::{exec, Slice},
+        parser::parse_sort,
+    },
+    csv_parser::{CsvDialect, LineTerminator},
+    describe::ColumnStats,
+    diagnostics::{CastFailure, CastReport, Diagnostic, ParseReport, Promotion, RowPolicy},
     filter::Filter,
+    formula::eval::Value,
+    histogram::{Binning, Histogram},
+    number_format::NumberFormat,
+    slice::{ColumnSlice, SliceValues},
+    sniffer::{sniff, Sniffed},
+    type_parser::{Codes, NullTokens},
     Frame,
 };
-use js_sys::JsString;
+use js_sys::{Array, Float64Array, Int32Array, JsString, Uint32Array, Uint8Array};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -41,23 +54,562 @@ impl Frame {
             .collect()
     }
 
-    #[wasm_bindgen(method)]
-    pub fn slice(&self, offset: usize, size: usize) -> Vec<JsString> {
-        self.columns
-            .iter()
-            .map(|column| column.join(offset, size))
-            .map(|s| JsString::from(s.as_str()))
+    #[wasm_bindgen(getter = dialect)]
+    pub fn js_dialect(&self) -> CsvDialect {
+        self.dialect()
+    }
+
+    #[wasm_bindgen(getter = sniffed)]
+    pub fn js_sniffed(&self) -> Option<Sniffed> {
+        self.sniffed
+    }
+
+    #[wasm_bindgen(getter = rowPolicy)]
+    pub fn row_policy(&self) -> RowPolicy {
+        self.report().policy
+    }
+
+    #[wasm_bindgen(setter = rowPolicy)]
+    pub fn js_set_row_policy(&mut self, policy: RowPolicy) {
+        self.set_row_policy(policy);
+    }
+
+    #[wasm_bindgen(getter = nullTokens)]
+    pub fn js_null_tokens(&self) -> Vec<JsString> {
+        self.null_tokens()
+            .tokens()
+            .map(|token| JsString::from(String::from_utf8_lossy(token).as_ref()))
             .collect()
     }
 
-    #[wasm_bindgen(method)]
-    pub fn distinct(&self, column: &str) -> Result<JsString, JsString> {
-        let value = self
-            .find_by_name(column)
+    #[wasm_bindgen(setter = nullTokens)]
+    pub fn js_set_null_tokens(&mut self, tokens: Vec<JsString>) {
+        self.set_null_tokens(NullTokens::new(tokens.into_iter().map(String::from)));
+    }
+
+    #[wasm_bindgen(getter = floatTolerance)]
+    pub fn js_float_tolerance(&self) -> f64 {
+        self.float_tolerance()
+    }
+
+    #[wasm_bindgen(setter = floatTolerance)]
+    pub fn js_set_float_tolerance(&mut self, tolerance: f64) {
+        self.set_float_tolerance(tolerance);
+    }
+
+    #[wasm_bindgen(js_name = setNumberFormat)]
+    pub fn js_set_number_format(
+        &mut self,
+        decimal: char,
+        thousands: Option<char>,
+    ) -> Result<(), JsString> {
+        let to_byte = |c: char| {
+            u8::try_from(c).map_err(|_| JsString::from("Expected a single byte character"))
+        };
+        let thousands = thousands.map(to_byte).transpose()?;
+        self.set_number_format(NumberFormat::new(to_byte(decimal)?, thousands));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = nullCount)]
+    pub fn null_count(&self, column: &str) -> Result<usize, JsString> {
+        self.column(column)
+            .map(Column::null_count)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = validCount)]
+    pub fn valid_count(&self, column: &str) -> Result<usize, JsString> {
+        self.column(column)
+            .map(Column::valid_count)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = parseReport)]
+    pub fn parse_report(&self) -> ParseReport {
+        self.report().clone()
+    }
+
+    #[wasm_bindgen(js_name = setColumnType)]
+    pub fn js_set_column_type(&mut self, name: &str, dtype: &str) -> Result<(), JsString> {
+        let dtype = Codes::try_from(dtype).map_err(|err| JsString::from(err.to_string()))?;
+        self.set_column_type(name, dtype)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = setColumnTypeAt)]
+    pub fn js_set_column_type_at(&mut self, index: usize, dtype: &str) -> Result<(), JsString> {
+        let dtype = Codes::try_from(dtype).map_err(|err| JsString::from(err.to_string()))?;
+        self.set_column_type_at(index, dtype)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = setFormat)]
+    pub fn js_set_format(&mut self, dtype: &str, format: &str) -> Result<(), JsString> {
+        let dtype = Codes::try_from(dtype).map_err(|err| JsString::from(err.to_string()))?;
+        self.set_format(dtype, format)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = castColumn)]
+    pub fn js_cast_column(&mut self, name: &str, dtype: &str) -> Result<CastReport, JsString> {
+        let dtype = Codes::try_from(dtype).map_err(|err| JsString::from(err.to_string()))?;
+        self.cast_column(name, dtype)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    /// One `ColumnSlice` per column.
+    pub fn slice(&self, offset: usize, size: usize) -> Array {
+        self.slice_columns(offset, size)
+            .into_iter()
+            .map(JsValue::from)
+            .collect()
+    }
+
+    /// Orders the rows, e.g. `Type 1, Attack DESC NULLS FIRST`.
+    #[wasm_bindgen(js_name = sortBy)]
+    pub fn js_sort_by(&mut self, keys: &str) -> Result<(), JsString> {
+        let (_, keys) = parse_sort(keys).map_err(|_| JsString::from("Cannot parse sort keys"))?;
+        self.sort_by(keys)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = clearSort)]
+    pub fn js_clear_sort(&mut self) {
+        self.clear_sort();
+    }
+
+    pub fn distinct(&self, column: &str) -> Result<Array, JsString> {
+        let values = self
+            .column(column)
+            .map_err(|err| JsString::from(err.to_string()))?
             .distinct()
             .map_err(|_| JsString::from("Cannot Hash Type"))?;
-        let ret = JsString::from(value.as_str());
-        Ok(ret)
+        Ok(to_array(&values))
+    }
+
+    /// Pairs of value, null for the missing ones, and count or share of the rows when normalized.
+    #[wasm_bindgen(js_name = valueCounts)]
+    pub fn value_counts(
+        &self,
+        column: &str,
+        limit: Option<usize>,
+        sort: Option<bool>,
+        normalize: Option<bool>,
+    ) -> Result<Array, JsString> {
+        let column = self
+            .column(column)
+            .map_err(|err| JsString::from(err.to_string()))?;
+        let counts = column
+            .value_counts(limit, sort.unwrap_or(true))
+            .map_err(|_| JsString::from("Cannot Hash Type"))?;
+        let total = column.len().max(1) as f64;
+        let pairs = counts.into_iter().map(|(value, count)| {
+            let count = match normalize {
+                Some(true) => count as f64 / total,
+                _ => count as f64,
+            };
+            let pair: Array = [
+                value.map_or(JsValue::NULL, JsValue::from),
+                JsValue::from(count),
+            ]
+            .into_iter()
+            .collect();
+            JsValue::from(pair)
+        });
+        Ok(pairs.collect())
+    }
+
+    /// Bins from the edges when given, else their number, else the strategy, Sturges by default.
+    #[wasm_bindgen(js_name = histogram)]
+    pub fn js_histogram(
+        &self,
+        column: &str,
+        bins: Option<usize>,
+        bin_edges: Option<Vec<f64>>,
+        strategy: Option<String>,
+    ) -> Result<Histogram, JsString> {
+        let binning = binning(bins, bin_edges, strategy)?;
+        self.histogram(column, &binning, None)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = describe)]
+    pub fn js_describe(&self) -> Array {
+        self.describe().into_iter().map(JsValue::from).collect()
+    }
+
+    /// Puts a formula like `=SUM(B2:B10)*C1` in a cell, gives back the addresses of the
+    /// recalculated cells.
+    #[wasm_bindgen(js_name = setFormula)]
+    pub fn js_set_formula(&mut self, cell: &str, formula: &str) -> Result<Array, JsString> {
+        let cells: Vec<String> = self
+            .set_formula(cell, formula)
+            .map_err(|err| JsString::from(err.to_string()))?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        Ok(to_array(&cells))
+    }
+
+    #[wasm_bindgen(js_name = clearFormula)]
+    pub fn js_clear_formula(&mut self, cell: &str) -> Result<Array, JsString> {
+        let cells: Vec<String> = self
+            .clear_formula(cell)
+            .map_err(|err| JsString::from(err.to_string()))?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        Ok(to_array(&cells))
+    }
+
+    #[wasm_bindgen(js_name = formula)]
+    pub fn js_formula(&self, cell: &str) -> Result<Option<String>, JsString> {
+        let formula = self
+            .formula(cell)
+            .map_err(|err| JsString::from(err.to_string()))?;
+        Ok(formula.map(String::from))
+    }
+
+    /// Number, string, boolean or null for an empty cell, error values are strings like `#DIV/0!`.
+    #[wasm_bindgen(js_name = cellValue)]
+    pub fn js_cell_value(&self, cell: &str) -> Result<JsValue, JsString> {
+        let value = self
+            .cell_value(cell)
+            .map_err(|err| JsString::from(err.to_string()))?;
+        Ok(match value {
+            Value::Empty => JsValue::NULL,
+            Value::Number(value) => JsValue::from(value),
+            Value::Bool(value) => JsValue::from(value),
+            value => JsValue::from(value.text()),
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl ColumnStats {
+    #[wasm_bindgen(getter)]
+    pub fn column(&self) -> JsString {
+        JsString::from(self.column.as_str())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dtype(&self) -> JsString {
+        JsString::from(self.dtype)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    #[wasm_bindgen(getter = nullCount)]
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn distinct(&self) -> usize {
+        self.distinct
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn mean(&self) -> Option<f64> {
+        self.numeric.map(|stats| stats.mean)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn std(&self) -> Option<f64> {
+        self.numeric.and_then(|stats| stats.std)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> Option<f64> {
+        self.numeric.map(|stats| stats.min)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn q1(&self) -> Option<f64> {
+        self.numeric.map(|stats| stats.q1)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn median(&self) -> Option<f64> {
+        self.numeric.map(|stats| stats.median)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn q3(&self) -> Option<f64> {
+        self.numeric.map(|stats| stats.q3)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> Option<f64> {
+        self.numeric.map(|stats| stats.max)
+    }
+
+    /// Pairs of value and count, most frequent first.
+    #[wasm_bindgen(getter)]
+    pub fn top(&self) -> Array {
+        let top = self.text.iter().flat_map(|stats| &stats.top);
+        top.map(|(value, count)| {
+            let pair: Array = [JsValue::from(value.as_str()), JsValue::from(*count)]
+                .into_iter()
+                .collect();
+            JsValue::from(pair)
+        })
+        .collect()
+    }
+
+    #[wasm_bindgen(getter = minLength)]
+    pub fn min_length(&self) -> Option<usize> {
+        self.text.as_ref().map(|stats| stats.min_length)
+    }
+
+    #[wasm_bindgen(getter = maxLength)]
+    pub fn max_length(&self) -> Option<usize> {
+        self.text.as_ref().map(|stats| stats.max_length)
+    }
+}
+
+#[wasm_bindgen]
+impl CsvDialect {
+    #[wasm_bindgen(getter)]
+    pub fn delimiter(&self) -> char {
+        self.delimiter.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn quote(&self) -> char {
+        self.quote.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn escape(&self) -> Option<char> {
+        self.escape.map(char::from)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn terminator(&self) -> LineTerminator {
+        self.terminator
+    }
+}
+
+#[wasm_bindgen]
+impl Sniffed {
+    #[wasm_bindgen(getter)]
+    pub fn dialect(&self) -> CsvDialect {
+        self.dialect
+    }
+
+    #[wasm_bindgen(getter = hasHeader)]
+    pub fn has_header(&self) -> bool {
+        self.has_header
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+}
+
+#[wasm_bindgen]
+impl ParseReport {
+    #[wasm_bindgen(getter)]
+    pub fn policy(&self) -> RowPolicy {
+        self.policy
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.diagnostics().len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<Diagnostic> {
+        self.diagnostics().get(index).cloned()
+    }
+
+    #[wasm_bindgen(getter = promotionCount)]
+    pub fn promotion_count(&self) -> usize {
+        self.promotions().len()
+    }
+
+    #[wasm_bindgen(js_name = getPromotion)]
+    pub fn get_promotion(&self, index: usize) -> Option<Promotion> {
+        self.promotions().get(index).cloned()
+    }
+}
+
+#[wasm_bindgen]
+impl Promotion {
+    #[wasm_bindgen(getter)]
+    pub fn column(&self) -> JsString {
+        JsString::from(self.column.as_str())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn from(&self) -> JsString {
+        JsString::from(self.from)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn to(&self) -> JsString {
+        JsString::from(self.to)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn chunk(&self) -> usize {
+        self.chunk
+    }
+}
+
+#[wasm_bindgen]
+impl CastReport {
+    #[wasm_bindgen(getter)]
+    pub fn column(&self) -> JsString {
+        JsString::from(self.column.as_str())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn from(&self) -> JsString {
+        JsString::from(self.from)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn to(&self) -> JsString {
+        JsString::from(self.to)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.failures().len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<CastFailure> {
+        self.failures().get(index).cloned()
+    }
+}
+
+#[wasm_bindgen]
+impl ColumnSlice {
+    #[wasm_bindgen(getter)]
+    pub fn dtype(&self) -> JsString {
+        JsString::from(self.dtype)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.len()
+    }
+
+    /// `Int32Array`, `Float64Array`, `Uint8Array` for booleans or an array of strings.
+    #[wasm_bindgen(getter)]
+    pub fn values(&self) -> JsValue {
+        match &self.values {
+            SliceValues::Boolean(values) => Uint8Array::from(&values[..]).into(),
+            SliceValues::Int32(values) => Int32Array::from(&values[..]).into(),
+            SliceValues::Float64(values) => Float64Array::from(&values[..]).into(),
+            SliceValues::Text(values) => to_array(values).into(),
+        }
+    }
+
+    /// One bit per row, set when the row holds a value.
+    #[wasm_bindgen(getter)]
+    pub fn validity(&self) -> Uint8Array {
+        Uint8Array::from(&self.validity[..])
+    }
+
+    #[wasm_bindgen(getter, js_name = text)]
+    pub fn js_text(&self) -> Array {
+        to_array(&self.text())
+    }
+}
+
+#[wasm_bindgen]
+impl Histogram {
+    #[wasm_bindgen(getter)]
+    pub fn edges(&self) -> Float64Array {
+        Float64Array::from(&self.edges[..])
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn counts(&self) -> Uint32Array {
+        let counts: Vec<u32> = self.counts.iter().map(|&count| count as u32).collect();
+        Uint32Array::from(&counts[..])
+    }
+}
+
+fn binning(
+    bins: Option<usize>,
+    bin_edges: Option<Vec<f64>>,
+    strategy: Option<String>,
+) -> Result<Binning, JsString> {
+    match (bin_edges, bins, strategy) {
+        (Some(edges), _, _) => {
+            Binning::edges(edges).ok_or_else(|| JsString::from("Expected increasing bin edges"))
+        }
+        (None, Some(bins), _) => Ok(Binning::Count(bins)),
+        (None, None, Some(strategy)) => Binning::strategy(&strategy)
+            .ok_or_else(|| JsString::from(format!("Unknown strategy {}", strategy))),
+        (None, None, None) => Ok(Binning::Sturges),
+    }
+}
+
+fn to_array(values: &[String]) -> Array {
+    values
+        .iter()
+        .map(|value| JsValue::from(value.as_str()))
+        .collect()
+}
+
+#[wasm_bindgen]
+impl CastFailure {
+    #[wasm_bindgen(getter)]
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> JsString {
+        JsString::from(self.value.as_str())
+    }
+}
+
+#[wasm_bindgen]
+impl Diagnostic {
+    #[wasm_bindgen(getter)]
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn actual(&self) -> usize {
+        self.actual
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn line(&self) -> JsString {
+        JsString::from(self.line.as_str())
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn js_to_string(&self) -> JsString {
+        JsString::from(self.to_string())
     }
 }
 
@@ -69,26 +621,64 @@ pub struct PollSource {
 
 #[wasm_bindgen]
 impl PollSource {
-    #[wasm_bindgen(method)]
-    pub fn slice(&self, frame: &Frame, offset: usize, size: usize) -> Vec<JsString> {
-        match &self.source {
+    pub fn slice(&self, frame: &Frame, offset: usize, size: usize) -> Array {
+        let columns = match &self.source {
             Slice::FilterSlice(filter) => filter.slice(frame, offset, size),
-        }
+            Slice::SortSlice(_) | Slice::AddColumnSlice(..) => frame.slice_columns(offset, size),
+            Slice::AggregateSlice(value) => vec![value.to_slice()],
+            Slice::GroupSlice(grouped) => grouped.slice_columns(offset, size),
+        };
+        columns.into_iter().map(JsValue::from).collect()
     }
 
     pub fn source_type(&self) -> JsString {
         JsString::from(self._type)
     }
+
+    /// The frame built by a group by, to be used like any other frame.
+    #[wasm_bindgen(js_name = intoFrame)]
+    pub fn into_frame(self) -> Option<Frame> {
+        match self.source {
+            Slice::GroupSlice(grouped) => Some(*grouped),
+            _ => None,
+        }
+    }
 }
 
 #[wasm_bindgen(js_name = processCommand)]
-pub fn process_command(input: &str, frame: &Frame) -> PollSource {
-    let slice = exec(input, frame).unwrap();
+pub fn process_command(input: &str, frame: &mut Frame) -> Result<PollSource, JsString> {
+    let slice = exec(input, frame).map_err(JsString::from)?;
     match slice {
-        Slice::FilterSlice(_) => PollSource {
+        Slice::FilterSlice(_) => Ok(PollSource {
             _type: "filter",
             source: slice,
-        },
+        }),
+        Slice::SortSlice(ref keys) => {
+            frame
+                .sort_by(keys.clone())
+                .map_err(|err| JsString::from(err.to_string()))?;
+            Ok(PollSource {
+                _type: "sort",
+                source: slice,
+            })
+        }
+        Slice::AddColumnSlice(ref name, ref expr) => {
+            frame
+                .add_column(name, expr.clone())
+                .map_err(|err| JsString::from(err.to_string()))?;
+            Ok(PollSource {
+                _type: "column",
+                source: slice,
+            })
+        }
+        Slice::AggregateSlice(_) => Ok(PollSource {
+            _type: "aggregate",
+            source: slice,
+        }),
+        Slice::GroupSlice(_) => Ok(PollSource {
+            _type: "group",
+            source: slice,
+        }),
     }
 }
 
@@ -102,17 +692,124 @@ pub fn new_frame() -> Frame {
     Frame::new()
 }
 
+#[wasm_bindgen(js_name = newDialect)]
+pub fn new_dialect(
+    delimiter: char,
+    quote: char,
+    escape: Option<char>,
+    terminator: LineTerminator,
+) -> Result<CsvDialect, JsString> {
+    let to_byte =
+        |c: char| u8::try_from(c).map_err(|_| JsString::from("Expected a single byte character"));
+    let escape = escape.map(to_byte).transpose()?;
+    Ok(CsvDialect::new(
+        to_byte(delimiter)?,
+        to_byte(quote)?,
+        escape,
+        terminator,
+    ))
+}
+
+#[wasm_bindgen(js_name = sniffDialect)]
+pub fn sniff_dialect(bytes: &[u8]) -> Sniffed {
+    sniff(bytes, &NullTokens::default())
+}
+
 #[wasm_bindgen(js_name = processStreamChunk)]
-pub fn process_stream_chunk(frame: &mut Frame, bytes: &[u8], skip_header: bool) {
-    frame.append(bytes, skip_header);
+pub fn process_stream_chunk(
+    frame: &mut Frame,
+    bytes: &[u8],
+    skip_header: Option<bool>,
+    dialect: Option<CsvDialect>,
+) -> Result<(), JsString> {
+    if let Some(dialect) = dialect {
+        frame
+            .set_dialect(dialect)
+            .map_err(|err| JsString::from(err.to_string()))?;
+    }
+    frame
+        .append(bytes, skip_header)
+        .map_err(|diagnostic| JsString::from(diagnostic.to_string()))
 }
 
 #[wasm_bindgen(js_name = processStreamTail)]
-pub fn process_stream_tail(frame: &mut Frame) {
-    frame.append_remainder();
+pub fn process_stream_tail(frame: &mut Frame) -> Result<(), JsString> {
+    frame
+        .append_remainder()
+        .map_err(|diagnostic| JsString::from(diagnostic.to_string()))
+}
+
+#[wasm_bindgen(js_name = addInFilter)]
+pub fn add_in_filter(
+    filter: &mut Filter,
+    frame: &Frame,
+    values: Vec<JsString>,
+    column: &str,
+) -> Result<(), JsString> {
+    let values: Vec<String> = values.into_iter().map(String::from).collect();
+    let values: Vec<&[u8]> = values.iter().map(|value| value.as_bytes()).collect();
+    filter
+        .add_in_filter(frame, &values, column)
+        .map_err(|err| JsString::from(err.to_string()))
+}
+
+#[wasm_bindgen(js_name = addBetweenFilter)]
+pub fn add_between_filter(
+    filter: &mut Filter,
+    frame: &Frame,
+    low: &str,
+    high: &str,
+    column: &str,
+    low_inclusive: Option<bool>,
+    high_inclusive: Option<bool>,
+) -> Result<(), JsString> {
+    filter
+        .add_between_filter(
+            frame,
+            low.as_bytes(),
+            high.as_bytes(),
+            column,
+            low_inclusive.unwrap_or(true),
+            high_inclusive.unwrap_or(true),
+        )
+        .map_err(|err| JsString::from(err.to_string()))
+}
+
+#[wasm_bindgen(js_name = addNullFilter)]
+pub fn add_null_filter(
+    filter: &mut Filter,
+    frame: &Frame,
+    column: &str,
+    is_null: bool,
+) -> Result<(), JsString> {
+    filter
+        .add_null_filter(frame, column, is_null)
+        .map_err(|err| JsString::from(err.to_string()))
 }
 
 #[wasm_bindgen(js_name = addEqualtoFilter)]
-pub fn add_equalto_filter(filter: &mut Filter, frame: &Frame, bytes: &[u8], column: &str) {
-    filter.add_equalto_filter(frame, bytes, column)
+pub fn add_equalto_filter(
+    filter: &mut Filter,
+    frame: &Frame,
+    bytes: &[u8],
+    column: &str,
+) -> Result<(), JsString> {
+    filter
+        .add_equalto_filter(frame, bytes, column)
+        .map_err(|err| JsString::from(err.to_string()))
+}
+
+#[wasm_bindgen(js_name = filteredHistogram)]
+pub fn filtered_histogram(
+    filter: &Filter,
+    frame: &Frame,
+    column: &str,
+    bins: Option<usize>,
+    bin_edges: Option<Vec<f64>>,
+    strategy: Option<String>,
+) -> Result<Histogram, JsString> {
+    let binning = binning(bins, bin_edges, strategy)?;
+    frame
+        .histogram(column, &binning, Some(filter.get()))
+        .map_err(|err| JsString::from(err.to_string()))
 }
@@ -1,11 +1,68 @@
 #[macro_export]
 macro_rules! equal_to_series {
     ($t:tt) => {
-        fn equal_to(&self, other: &dyn SeriesTrait) -> $crate::series::errors::FilterResult {
-            let set = other.$t()?.iter().collect::<std::collections::HashSet<_>>();
+        fn equal_to(&self, other: &dyn SeriesTrait) -> $crate::series::errors::FilterResult<'_> {
+            let set = other
+                .$t()?
+                .iter()
+                .flatten()
+                .collect::<std::collections::HashSet<_>>();
+            let ret = self
+                .iter()
+                .map(move |el| el.as_ref().is_some_and(|el| set.contains(el)))
+                .collect::<bitvec::prelude::BitVec>();
+
+            Ok(ret)
+        }
+    };
+}
+
+/// Floats are matched within a tolerance, NaN only matches NaN.
+#[macro_export]
+macro_rules! float_equal_to_series {
+    ($t:tt) => {
+        fn equal_to(&self, other: &dyn SeriesTrait) -> $crate::series::errors::FilterResult<'_> {
+            self.equal_to_within(other, 0.0)
+        }
+
+        fn equal_to_within(
+            &self,
+            other: &dyn SeriesTrait,
+            tolerance: f64,
+        ) -> $crate::series::errors::FilterResult<'_> {
+            let values = other.$t()?.iter().flatten().copied().collect::<Vec<_>>();
+            let matches = |el: $t| {
+                values.iter().any(|&value| {
+                    (el.is_nan() && value.is_nan())
+                        || el == value
+                        || f64::from(el - value).abs() <= tolerance
+                })
+            };
             let ret = self
                 .iter()
-                .map(move |el| set.contains(el))
+                .map(|el| el.is_some_and(matches))
+                .collect::<bitvec::prelude::BitVec>();
+
+            Ok(ret)
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! compare_series {
+    ($t:tt) => {
+        fn compare(
+            &self,
+            other: &dyn SeriesTrait,
+            op: $crate::filter::Comparison,
+        ) -> $crate::series::errors::FilterResult<'_> {
+            let value = other.$t()?.first().and_then(Option::as_ref);
+            let ret = self
+                .iter()
+                .map(|el| match (el.as_ref(), value) {
+                    (Some(el), Some(value)) => op.test(el, value),
+                    _ => false,
+                })
                 .collect::<bitvec::prelude::BitVec>();
 
             Ok(ret)
@@ -28,52 +85,174 @@ macro_rules! sum_series {
 }
 
 #[macro_export]
-macro_rules! join_series {
+macro_rules! gather_series {
+    ($variant:ident) => {
+        fn gather(&self, rows: &[usize]) -> $crate::column::SeriesEnum {
+            let series = rows.iter().map(|&row| self[row].clone()).collect();
+            $crate::column::SeriesEnum::$variant(Box::new(series))
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! aggregate_series {
+    ($wrap:expr) => {
+        fn aggregate(
+            &self,
+            rows: &[usize],
+            aggregate: $crate::aggregate::Aggregate,
+        ) -> Result<$crate::aggregate::Scalar, $crate::series::errors::WrongType> {
+            let values = rows
+                .iter()
+                .filter_map(|&row| self.get(row).copied().flatten())
+                .map($wrap);
+            Ok($crate::aggregate::aggregate(values, aggregate))
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! take_series {
+    ($variant:ident) => {
+        fn take(&self, rows: &[usize]) -> $crate::slice::ColumnSlice {
+            let values = rows.iter().map(|&row| self.get(row).copied().flatten());
+            $crate::slice::ColumnSlice::new(values, $crate::slice::SliceValues::$variant)
+        }
+    };
+    // Integers too wide for a float keep their exact text aside
+    (exact, $conv:expr) => {
+        fn take(&self, rows: &[usize]) -> $crate::slice::ColumnSlice {
+            let values = rows.iter().map(|&row| self.get(row).copied().flatten());
+            let text = values
+                .clone()
+                .map(|el| el.map_or("".into(), |x| x.to_string()))
+                .collect();
+            let values = values.map(|el| el.map($conv));
+            $crate::slice::ColumnSlice::new(values, $crate::slice::SliceValues::Float64)
+                .with_formatted(text)
+        }
+    };
+    ($variant:ident, $conv:expr) => {
+        fn take(&self, rows: &[usize]) -> $crate::slice::ColumnSlice {
+            let values = rows
+                .iter()
+                .map(|&row| self.get(row).copied().flatten().map($conv));
+            $crate::slice::ColumnSlice::new(values, $crate::slice::SliceValues::$variant)
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! cmp_rows_series {
     () => {
-        fn join(&self, offset: usize, size: usize) -> String {
-            self.iter()
-                .skip(offset)
-                .take(size)
-                .map(|opt| opt.map_or("".into(), |number| number.to_string()))
-                .intersperse($crate::series::DELIMITER_TOKEN.into())
-                .collect()
+        fn cmp_rows(&self, a: usize, b: usize) -> core::cmp::Ordering {
+            self[a].cmp(&self[b])
+        }
+    };
+    (float) => {
+        fn cmp_rows(&self, a: usize, b: usize) -> core::cmp::Ordering {
+            match (self[a], self[b]) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! null_mask_series {
+    () => {
+        fn null_mask(&self) -> bitvec::prelude::BitVec {
+            self.iter().map(Option::is_none).collect()
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! null_count_series {
+    () => {
+        fn null_count(&self) -> usize {
+            self.iter().filter(|el| el.is_none()).count()
         }
     };
 }
 
 #[macro_export]
-macro_rules! filter_join {
+macro_rules! words_series {
     () => {
-        fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String {
+        fn words(&self) -> Words {
+            let mut words = Words::default();
             self.iter()
-                .zip(mask)
-                .filter_map(|(opt, mask_el)| {
-                    mask_el.then(|| opt.map_or("".into(), |el| el.to_string()))
-                })
-                .skip(offset)
-                .take(size)
-                .intersperse(DELIMITER_TOKEN.into())
-                .collect::<String>()
+                .map(|opt| opt.map_or("".into(), |el| el.to_string()))
+                .for_each(|word| words.extend(word.as_bytes()));
+            words
         }
     };
 }
 
 #[macro_export]
-macro_rules! distinct {
+macro_rules! value_counts_series {
+    () => {
+        fn value_counts(
+            &self,
+        ) -> Result<Vec<(Option<String>, usize)>, $crate::series::errors::NonHashable> {
+            let values = self.iter().map(Option::as_ref);
+            Ok($crate::series::count_values(values, ToString::to_string))
+        }
+    };
+    // Every NaN counts as the same value
+    (float) => {
+        fn value_counts(
+            &self,
+        ) -> Result<Vec<(Option<String>, usize)>, $crate::series::errors::NonHashable> {
+            let values = self.iter().map(|el| el.map(ordered_float::OrderedFloat));
+            Ok($crate::series::count_values(values, |value| {
+                value.0.to_string()
+            }))
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! fits_series {
     ($t:tt) => {
-        fn distinct(&self) -> Result<String, NonHashable> {
-            let set = self
-                .$t()
-                .unwrap()
-                .iter()
-                .collect::<std::collections::HashSet<_>>();
-            let ret = set
-                .iter()
-                .map(|&el| el.map_or("".into(), |el| el.to_string()))
-                .intersperse(DELIMITER_TOKEN.into())
-                .collect();
+        fn fits(&self, word: &[u8]) -> bool {
+            word.is_empty() || lexical::parse::<$t, _>(word).is_ok()
+        }
+    };
+}
 
-            Ok(ret)
+#[macro_export]
+macro_rules! promote_series {
+    () => {
+        fn promote(&self, code: $crate::type_parser::Codes) -> Option<$crate::column::SeriesEnum> {
+            use $crate::{column::SeriesEnum, type_parser::Codes};
+
+            match code {
+                Codes::Int64 => {
+                    let series = self
+                        .iter()
+                        .map(|el| el.and_then(|x| num::ToPrimitive::to_i64(&x)));
+                    Some(SeriesEnum::I64(Box::new(series.collect())))
+                }
+                Codes::Int128 => {
+                    let series = self
+                        .iter()
+                        .map(|el| el.and_then(|x| num::ToPrimitive::to_i128(&x)));
+                    Some(SeriesEnum::I128(Box::new(series.collect())))
+                }
+                Codes::Float64 => {
+                    let series = self
+                        .iter()
+                        .map(|el| el.and_then(|x| num::ToPrimitive::to_f64(&x)));
+                    Some(SeriesEnum::F64(Box::new(series.collect())))
+                }
+                Codes::Any => {
+                    let series = self.iter().map(|el| el.map(|x| x.to_string()));
+                    Some(SeriesEnum::Any(Box::new(series.collect())))
+                }
+                _ => None,
+            }
         }
     };
 }
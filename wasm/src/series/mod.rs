@@ -1,18 +1,33 @@
 pub mod errors;
 pub mod macros;
+pub mod temporal;
 
-use bitvec::slice::BitSlice;
+use core::{cmp::Ordering, hash::Hash};
+use std::collections::HashMap;
+
+use bitvec::prelude::BitVec;
+use chrono::NaiveDate;
 use lexical::parse;
 use num::Num;
 
 use crate::{
-    distinct, equal_to_series, filter_join, join_series, sum_series, type_parser::bytes_to_bool,
-    Words,
+    aggregate::{Aggregate, Scalar},
+    aggregate_series, cmp_rows_series,
+    column::SeriesEnum,
+    compare_series, equal_to_series,
+    filter::{Comparison, TextPredicate},
+    fits_series, float_equal_to_series, gather_series, null_count_series, null_mask_series,
+    promote_series,
+    slice::{widen_f32, ColumnSlice, SliceValues},
+    sum_series, take_series,
+    type_parser::{bytes_to_bool, Codes},
+    value_counts_series, words_series, Words,
 };
 
-use self::errors::{FilterResult, NonHashable, ViewResult, WrongType};
-
-pub const DELIMITER_TOKEN: &str = "DELIMITER_TOKEN";
+use self::{
+    errors::{FilterResult, NonHashable, ViewResult, WrongType},
+    temporal::TemporalSeries,
+};
 
 pub trait Numeric: Copy + Default + Num {}
 impl Numeric for i32 {}
@@ -24,15 +39,51 @@ impl Numeric for f64 {}
 pub trait SeriesTrait {
     fn len(&self) -> usize;
     fn is_empty(&self) -> bool;
+    fn null_count(&self) -> usize;
+    /// One bit per row, set where the value is missing.
+    fn null_mask(&self) -> BitVec;
+    /// Order of two rows, nulls first.
+    fn cmp_rows(&self, a: usize, b: usize) -> Ordering;
     fn extend_from_words(&mut self, words: Words);
-    fn join(&self, offset: usize, size: usize) -> String;
+    /// Settles how the words are read before the first of them is, only temporal series
+    /// have a choice to make.
+    fn settle(&mut self, _words: &Words) {}
+    /// Whether a word can be parsed into this series without turning into a null.
+    fn fits(&self, _word: &[u8]) -> bool {
+        true
+    }
+    /// Converts the series to a wider type, see `Codes::promote`.
+    fn promote(&self, _code: Codes) -> Option<SeriesEnum> {
+        None
+    }
+    /// The values as text, nulls become empty words.
+    fn words(&self) -> Words;
+    /// The values at the given rows.
+    fn take(&self, rows: &[usize]) -> ColumnSlice;
+    /// A new series of the same type holding the given rows.
+    fn gather(&self, rows: &[usize]) -> SeriesEnum;
     fn sum(&self) -> Result<Box<dyn SeriesTrait>, &str> {
         Err("Cannot sum this type")
     }
-    fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String;
+    /// Aggregates the non null values at the given rows, only numeric columns have any.
+    fn aggregate(&self, _rows: &[usize], _aggregate: Aggregate) -> Result<Scalar, WrongType> {
+        Err(WrongType)
+    }
     fn equal_to(&self, _other: &dyn SeriesTrait) -> FilterResult {
         Err(WrongType)
     }
+    /// Same as `equal_to`, floats match when they are at most `tolerance` apart.
+    fn equal_to_within(&self, other: &dyn SeriesTrait, _tolerance: f64) -> FilterResult<'_> {
+        self.equal_to(other)
+    }
+    /// Text predicates, only text columns have any.
+    fn matches(&self, _predicate: &TextPredicate) -> FilterResult<'_> {
+        Err(WrongType)
+    }
+    /// Compares every value with the first value of `other`, strings compare lexicographically.
+    fn compare(&self, _other: &dyn SeriesTrait, _op: Comparison) -> FilterResult<'_> {
+        Err(WrongType)
+    }
     fn i32(&self) -> ViewResult<i32> {
         Err(WrongType)
     }
@@ -54,9 +105,115 @@ pub trait SeriesTrait {
     fn str(&self) -> ViewResult<String> {
         Err(WrongType)
     }
-    fn distinct(&self) -> Result<String, NonHashable> {
+    fn date(&self) -> ViewResult<'_, NaiveDate> {
+        Err(WrongType)
+    }
+    /// Each value with the number of rows holding it, nulls included, in order of first appearance.
+    fn value_counts(&self) -> Result<Vec<(Option<String>, usize)>, NonHashable> {
         Err(NonHashable)
     }
+    /// The values of `value_counts`, nulls become empty strings.
+    fn distinct(&self) -> Result<Vec<String>, NonHashable> {
+        let counts = self.value_counts()?;
+        Ok(counts
+            .into_iter()
+            .map(|(value, _)| value.unwrap_or_default())
+            .collect())
+    }
+}
+
+/// Counts the values in order of first appearance, rendering each distinct value once.
+pub fn count_values<T: Copy + Hash + Eq>(
+    values: impl Iterator<Item = Option<T>>,
+    render: impl Fn(T) -> String,
+) -> Vec<(Option<String>, usize)> {
+    let mut ids: HashMap<Option<T>, usize> = HashMap::new();
+    let mut counts: Vec<(Option<T>, usize)> = Vec::new();
+    for value in values {
+        match ids.get(&value) {
+            Some(&id) => counts[id].1 += 1,
+            None => {
+                ids.insert(value, counts.len());
+                counts.push((value, 1));
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(value, count)| (value.map(&render), count))
+        .collect()
+}
+
+/// A column without any value yet, it takes the type of the first values it gets.
+#[derive(Default)]
+pub struct NullSeries(pub usize);
+
+impl SeriesTrait for NullSeries {
+    fn len(&self) -> usize {
+        self.0
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    fn null_count(&self) -> usize {
+        self.0
+    }
+
+    fn null_mask(&self) -> BitVec {
+        BitVec::repeat(true, self.0)
+    }
+
+    fn cmp_rows(&self, _a: usize, _b: usize) -> Ordering {
+        Ordering::Equal
+    }
+
+    fn extend_from_words(&mut self, words: Words) {
+        self.0 += words.len();
+    }
+
+    fn fits(&self, word: &[u8]) -> bool {
+        word.is_empty()
+    }
+
+    fn promote(&self, code: Codes) -> Option<SeriesEnum> {
+        let n = self.0;
+        let series = match code {
+            Codes::Boolean => SeriesEnum::Bool(Box::new(vec![None; n])),
+            Codes::Int32 => SeriesEnum::I32(Box::new(vec![None; n])),
+            Codes::Int64 => SeriesEnum::I64(Box::new(vec![None; n])),
+            Codes::Int128 => SeriesEnum::I128(Box::new(vec![None; n])),
+            Codes::Float32 => SeriesEnum::F32(Box::new(vec![None; n])),
+            Codes::Float64 => SeriesEnum::F64(Box::new(vec![None; n])),
+            Codes::Date => SeriesEnum::Date(Box::new(TemporalSeries::new(vec![None; n], None))),
+            Codes::Time => SeriesEnum::Time(Box::new(TemporalSeries::new(vec![None; n], None))),
+            Codes::Timestamp => {
+                SeriesEnum::Timestamp(Box::new(TemporalSeries::new(vec![None; n], None)))
+            }
+            Codes::Any => SeriesEnum::Any(Box::new(vec![None; n])),
+            _ => return None,
+        };
+        Some(series)
+    }
+
+    fn words(&self) -> Words {
+        let mut words = Words::default();
+        (0..self.0).for_each(|_| words.extend(b""));
+        words
+    }
+
+    fn take(&self, rows: &[usize]) -> ColumnSlice {
+        ColumnSlice::new(rows.iter().map(|_| None::<String>), SliceValues::Text)
+    }
+
+    fn gather(&self, rows: &[usize]) -> SeriesEnum {
+        SeriesEnum::Null(Box::new(NullSeries(rows.len())))
+    }
+
+    fn value_counts(&self) -> Result<Vec<(Option<String>, usize)>, NonHashable> {
+        Ok((self.0 > 0).then_some((None, self.0)).into_iter().collect())
+    }
 }
 
 impl SeriesTrait for Vec<Option<bool>> {
@@ -68,10 +225,23 @@ impl SeriesTrait for Vec<Option<bool>> {
         self.is_empty()
     }
 
+    null_count_series!();
+    null_mask_series!();
+    cmp_rows_series!();
+
     fn bool(&self) -> ViewResult<bool> {
         Ok(&self[..])
     }
 
+    fn fits(&self, word: &[u8]) -> bool {
+        word.is_empty() || bytes_to_bool(word).is_some()
+    }
+
+    fn promote(&self, code: Codes) -> Option<SeriesEnum> {
+        let series = self.iter().map(|el| el.map(|b| b.to_string()));
+        (code == Codes::Any).then(|| SeriesEnum::Any(Box::new(series.collect())))
+    }
+
     fn extend_from_words(&mut self, bytes: Words) {
         bytes.into_iter().for_each(|words| {
             let el = bytes_to_bool(words);
@@ -79,26 +249,13 @@ impl SeriesTrait for Vec<Option<bool>> {
         });
     }
 
-    fn join(&self, offset: usize, size: usize) -> String {
-        self.iter()
-            .skip(offset)
-            .take(size)
-            .map(|opt| opt.map_or("".into(), |b| b.to_string()))
-            .intersperse(DELIMITER_TOKEN.into())
-            .collect()
-    }
+    words_series!();
 
-    fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String {
-        self.iter()
-            .zip(mask)
-            .filter_map(|(opt, mask_el)| {
-                mask_el.then(|| opt.map_or("".into(), |el| el.to_string()))
-            })
-            .skip(offset)
-            .take(size)
-            .intersperse(DELIMITER_TOKEN.into())
-            .collect::<String>()
-    }
+    take_series!(Boolean, u8::from);
+    gather_series!(Bool);
+    equal_to_series!(bool);
+    compare_series!(bool);
+    value_counts_series!();
 }
 
 impl SeriesTrait for Vec<Option<String>> {
@@ -110,37 +267,49 @@ impl SeriesTrait for Vec<Option<String>> {
         self.is_empty()
     }
 
+    null_count_series!();
+    null_mask_series!();
+    cmp_rows_series!();
+
     fn str(&self) -> ViewResult<String> {
         Ok(&self[..])
     }
 
     fn extend_from_words(&mut self, bytes: Words) {
         bytes.into_iter().for_each(|word| {
-            let el = String::from_utf8(word.into()).ok();
+            let el = match word {
+                [] => None,
+                word => String::from_utf8(word.into()).ok(),
+            };
             self.push(el);
         })
     }
 
-    fn join(&self, offset: usize, size: usize) -> String {
+    fn words(&self) -> Words {
+        let mut words = Words::default();
         self.iter()
-            .skip(offset)
-            .take(size)
-            .map(|opt| opt.as_deref().unwrap_or_default())
-            .intersperse(DELIMITER_TOKEN)
-            .collect()
+            .for_each(|opt| words.extend(opt.as_deref().unwrap_or_default().as_bytes()));
+        words
     }
 
-    fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String {
-        self.iter()
-            .zip(mask)
-            .filter_map(|(opt, mask_el)| mask_el.then(|| opt.as_deref().unwrap_or_default()))
-            .skip(offset)
-            .take(size)
-            .intersperse(DELIMITER_TOKEN)
-            .collect()
+    fn take(&self, rows: &[usize]) -> ColumnSlice {
+        let values = rows.iter().map(|&row| self.get(row).cloned().flatten());
+        ColumnSlice::new(values, SliceValues::Text)
     }
 
+    gather_series!(Any);
     equal_to_series!(str);
+
+    fn matches(&self, predicate: &TextPredicate) -> FilterResult<'_> {
+        let ret = self
+            .iter()
+            .map(|el| el.as_deref().is_some_and(|el| predicate.test(el)))
+            .collect();
+
+        Ok(ret)
+    }
+    compare_series!(str);
+    value_counts_series!();
 }
 
 impl SeriesTrait for Vec<Option<i32>> {
@@ -152,6 +321,10 @@ impl SeriesTrait for Vec<Option<i32>> {
         self.is_empty()
     }
 
+    null_count_series!();
+    null_mask_series!();
+    cmp_rows_series!();
+
     fn i32(&self) -> ViewResult<i32> {
         Ok(&self[..])
     }
@@ -163,11 +336,16 @@ impl SeriesTrait for Vec<Option<i32>> {
         })
     }
 
-    join_series!();
-    filter_join!();
+    take_series!(Int32);
+    words_series!();
     sum_series!(i32);
+    gather_series!(I32);
+    aggregate_series!(|x| Scalar::Int(x.into()));
+    fits_series!(i32);
+    promote_series!();
     equal_to_series!(i32);
-    distinct!(i32);
+    compare_series!(i32);
+    value_counts_series!();
 }
 
 impl SeriesTrait for Vec<Option<i64>> {
@@ -179,6 +357,10 @@ impl SeriesTrait for Vec<Option<i64>> {
         self.is_empty()
     }
 
+    null_count_series!();
+    null_mask_series!();
+    cmp_rows_series!();
+
     fn i64(&self) -> ViewResult<i64> {
         Ok(&self[..])
     }
@@ -190,10 +372,16 @@ impl SeriesTrait for Vec<Option<i64>> {
         })
     }
 
-    join_series!();
+    take_series!(exact, |x| x as f64);
+    words_series!();
     sum_series!(i64);
+    gather_series!(I64);
+    aggregate_series!(|x| Scalar::Int(x.into()));
+    fits_series!(i64);
+    promote_series!();
     equal_to_series!(i64);
-    filter_join!();
+    compare_series!(i64);
+    value_counts_series!();
 }
 
 impl SeriesTrait for Vec<Option<i128>> {
@@ -205,6 +393,10 @@ impl SeriesTrait for Vec<Option<i128>> {
         self.is_empty()
     }
 
+    null_count_series!();
+    null_mask_series!();
+    cmp_rows_series!();
+
     fn i128(&self) -> ViewResult<i128> {
         Ok(&self[..])
     }
@@ -216,10 +408,16 @@ impl SeriesTrait for Vec<Option<i128>> {
         })
     }
 
-    join_series!();
+    take_series!(exact, |x| x as f64);
+    words_series!();
     sum_series!(i128);
+    gather_series!(I128);
+    aggregate_series!(Scalar::Int);
+    fits_series!(i128);
+    promote_series!();
     equal_to_series!(i128);
-    filter_join!();
+    compare_series!(i128);
+    value_counts_series!();
 }
 
 impl SeriesTrait for Vec<Option<f32>> {
@@ -231,6 +429,10 @@ impl SeriesTrait for Vec<Option<f32>> {
         self.is_empty()
     }
 
+    null_count_series!();
+    null_mask_series!();
+    cmp_rows_series!(float);
+
     fn f32(&self) -> ViewResult<f32> {
         Ok(&self[..])
     }
@@ -242,9 +444,16 @@ impl SeriesTrait for Vec<Option<f32>> {
         })
     }
 
-    join_series!();
+    take_series!(Float64, widen_f32);
+    words_series!();
     sum_series!(f32);
-    filter_join!();
+    gather_series!(F32);
+    aggregate_series!(|x| Scalar::Float(widen_f32(x)));
+    float_equal_to_series!(f32);
+    compare_series!(f32);
+    fits_series!(f32);
+    promote_series!();
+    value_counts_series!(float);
 }
 
 impl SeriesTrait for Vec<Option<f64>> {
@@ -256,6 +465,10 @@ impl SeriesTrait for Vec<Option<f64>> {
         self.is_empty()
     }
 
+    null_count_series!();
+    null_mask_series!();
+    cmp_rows_series!(float);
+
     fn f64(&self) -> ViewResult<f64> {
         Ok(&self[..])
     }
@@ -267,7 +480,14 @@ impl SeriesTrait for Vec<Option<f64>> {
         })
     }
 
-    join_series!();
+    take_series!(Float64);
+    words_series!();
     sum_series!(f64);
-    filter_join!();
+    gather_series!(F64);
+    aggregate_series!(Scalar::Float);
+    float_equal_to_series!(f64);
+    compare_series!(f64);
+    fits_series!(f64);
+    promote_series!();
+    value_counts_series!(float);
 }
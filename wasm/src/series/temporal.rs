@@ -0,0 +1,380 @@
+use core::fmt::Write;
+use std::{cmp::Ordering, collections::HashSet, hash::Hash};
+
+use bitvec::prelude::BitVec;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::{
+    column::SeriesEnum,
+    filter::Comparison,
+    series::{
+        count_values,
+        errors::{FilterResult, NonHashable, ViewResult, WrongType},
+        SeriesTrait,
+    },
+    slice::{ColumnSlice, SliceValues},
+    type_parser::Codes,
+    Words,
+};
+
+/// A point in time kept in UTC, along with the offset it was written with, if any.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    pub utc: NaiveDateTime,
+    pub offset: Option<i32>,
+}
+
+impl Timestamp {
+    pub fn local(&self) -> Option<DateTime<FixedOffset>> {
+        let offset = FixedOffset::east_opt(self.offset?)?;
+        Some(DateTime::from_naive_utc_and_offset(self.utc, offset))
+    }
+}
+
+pub trait TemporalValue: Copy + Ord + Hash {
+    /// Formats tried in order when the column has none of its own, the first one is used
+    /// for output.
+    const FORMATS: &'static [&'static str];
+    /// Formats tried after `FORMATS` that read the same text as different values,
+    /// `01/02/2020` is the 2nd of January month first and the 1st of February day first.
+    /// A column reads with one of them only, month first comes before day first.
+    const CONVENTIONS: &'static [&'static [&'static str]] = &[];
+
+    fn parse_with(word: &str, format: &str) -> Option<Self>;
+    fn render(&self, format: Option<&str>) -> String;
+    fn wrap(series: TemporalSeries<Self>) -> SeriesEnum;
+
+    fn to_timestamp(self) -> Option<Timestamp> {
+        None
+    }
+
+    fn dates(_series: &TemporalSeries<Self>) -> ViewResult<'_, NaiveDate> {
+        Err(WrongType)
+    }
+
+    /// Reads the word with the format when there is one, with any known format otherwise.
+    fn parse(word: &str, format: Option<&str>) -> Option<Self> {
+        let word = word.trim();
+        match format {
+            Some(format) => Self::parse_with(word, format),
+            None => Self::FORMATS
+                .iter()
+                .chain(Self::CONVENTIONS.iter().copied().flatten())
+                .find_map(|format| Self::parse_with(word, format)),
+        }
+    }
+}
+
+/// Formats without panicking, specifiers the value cannot fill give an empty string.
+fn render<T: core::fmt::Display>(formatted: T) -> String {
+    let mut ret = String::new();
+    let _ = write!(ret, "{}", formatted);
+    ret
+}
+
+impl TemporalValue for NaiveDate {
+    const FORMATS: &'static [&'static str] = &["%Y-%m-%d"];
+    const CONVENTIONS: &'static [&'static [&'static str]] = &[&["%m/%d/%Y"], &["%d/%m/%Y"]];
+
+    fn parse_with(word: &str, format: &str) -> Option<Self> {
+        NaiveDate::parse_from_str(word, format).ok()
+    }
+
+    fn render(&self, format: Option<&str>) -> String {
+        render(self.format(format.unwrap_or(Self::FORMATS[0])))
+    }
+
+    fn wrap(series: TemporalSeries<Self>) -> SeriesEnum {
+        SeriesEnum::Date(Box::new(series))
+    }
+
+    fn dates(series: &TemporalSeries<Self>) -> ViewResult<'_, NaiveDate> {
+        Ok(series.values())
+    }
+
+    fn to_timestamp(self) -> Option<Timestamp> {
+        Some(Timestamp {
+            utc: self.and_time(NaiveTime::MIN),
+            offset: None,
+        })
+    }
+}
+
+impl TemporalValue for NaiveTime {
+    const FORMATS: &'static [&'static str] = &["%H:%M:%S%.f", "%H:%M"];
+
+    fn parse_with(word: &str, format: &str) -> Option<Self> {
+        NaiveTime::parse_from_str(word, format).ok()
+    }
+
+    fn render(&self, format: Option<&str>) -> String {
+        render(self.format(format.unwrap_or(Self::FORMATS[0])))
+    }
+
+    fn wrap(series: TemporalSeries<Self>) -> SeriesEnum {
+        SeriesEnum::Time(Box::new(series))
+    }
+}
+
+impl TemporalValue for Timestamp {
+    const FORMATS: &'static [&'static str] = &[
+        "%Y-%m-%dT%H:%M:%S%.f%#z",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f%#z",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ];
+    const CONVENTIONS: &'static [&'static [&'static str]] = &[
+        &["%m/%d/%Y %H:%M:%S%.f", "%m/%d/%Y %H:%M"],
+        &["%d/%m/%Y %H:%M:%S%.f", "%d/%m/%Y %H:%M"],
+    ];
+
+    fn parse_with(word: &str, format: &str) -> Option<Self> {
+        match DateTime::parse_from_str(word, format) {
+            Ok(datetime) => Some(Timestamp {
+                utc: datetime.naive_utc(),
+                offset: Some(datetime.offset().local_minus_utc()),
+            }),
+            Err(_) => NaiveDateTime::parse_from_str(word, format)
+                .ok()
+                .map(|utc| Timestamp { utc, offset: None }),
+        }
+    }
+
+    fn render(&self, format: Option<&str>) -> String {
+        match self.local() {
+            Some(local) => render(local.format(format.unwrap_or("%Y-%m-%dT%H:%M:%S%.f%:z"))),
+            None => render(self.utc.format(format.unwrap_or("%Y-%m-%dT%H:%M:%S%.f"))),
+        }
+    }
+
+    fn wrap(series: TemporalSeries<Self>) -> SeriesEnum {
+        SeriesEnum::Timestamp(Box::new(series))
+    }
+
+    fn to_timestamp(self) -> Option<Timestamp> {
+        Some(self)
+    }
+}
+
+/// Temporal values along with the format used to read and write them. Without a format
+/// the column settles on one of the conventions of its type, by index.
+#[derive(Clone, Debug)]
+pub struct TemporalSeries<T> {
+    values: Vec<Option<T>>,
+    format: Option<String>,
+    convention: Option<usize>,
+}
+
+impl<T: TemporalValue> TemporalSeries<T> {
+    pub fn new(values: Vec<Option<T>>, format: Option<&str>) -> Self {
+        Self {
+            values,
+            format: format.map(String::from),
+            convention: None,
+        }
+    }
+
+    /// Reads with the column's format only, or with the formats of its type along with
+    /// the convention it settled on. Every convention is tried until then.
+    fn read(&self, word: &[u8]) -> Option<T> {
+        let word = std::str::from_utf8(word).ok()?.trim();
+        if let Some(format) = &self.format {
+            return T::parse_with(word, format);
+        }
+        let conventions = match self.convention {
+            Some(i) => &T::CONVENTIONS[i..=i],
+            None => T::CONVENTIONS,
+        };
+        T::FORMATS
+            .iter()
+            .chain(conventions.iter().copied().flatten())
+            .find_map(|format| T::parse_with(word, format))
+    }
+
+    pub fn values(&self) -> &[Option<T>] {
+        &self.values
+    }
+
+    fn render(&self, value: &Option<T>) -> String {
+        value.map_or("".into(), |value| value.render(self.format.as_deref()))
+    }
+
+    /// Filters hand their values over as text, read here with the column's format.
+    fn needles(&self, other: &dyn SeriesTrait) -> Result<Vec<T>, WrongType> {
+        let words = other.str()?;
+        let values = words
+            .iter()
+            .flatten()
+            .filter_map(|word| self.read(word.as_bytes()));
+        Ok(values.collect())
+    }
+}
+
+impl<T: TemporalValue> SeriesTrait for TemporalSeries<T> {
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn null_count(&self) -> usize {
+        self.values.iter().filter(|el| el.is_none()).count()
+    }
+
+    fn null_mask(&self) -> BitVec {
+        self.values.iter().map(Option::is_none).collect()
+    }
+
+    fn cmp_rows(&self, a: usize, b: usize) -> Ordering {
+        self.values[a].cmp(&self.values[b])
+    }
+
+    fn extend_from_words(&mut self, words: Words) {
+        words.into_iter().for_each(|word| {
+            let el = self.read(word);
+            self.values.push(el);
+        })
+    }
+
+    /// Picks the convention reading most of the words among those needing one.
+    fn settle(&mut self, words: &Words) {
+        if self.format.is_some() || self.convention.is_some() {
+            return;
+        }
+        let mut counts = vec![0; T::CONVENTIONS.len()];
+        for word in words {
+            let word = std::str::from_utf8(word).unwrap_or_default().trim();
+            if T::FORMATS
+                .iter()
+                .any(|format| T::parse_with(word, format).is_some())
+            {
+                continue;
+            }
+            for (count, formats) in counts.iter_mut().zip(T::CONVENTIONS) {
+                if formats
+                    .iter()
+                    .any(|format| T::parse_with(word, format).is_some())
+                {
+                    *count += 1;
+                }
+            }
+        }
+        self.convention = (0..counts.len())
+            .filter(|&i| counts[i] > 0)
+            .max_by_key(|&i| (counts[i], core::cmp::Reverse(i)));
+    }
+
+    fn fits(&self, word: &[u8]) -> bool {
+        word.is_empty() || self.read(word).is_some()
+    }
+
+    fn promote(&self, code: Codes) -> Option<SeriesEnum> {
+        match code {
+            Codes::Timestamp => {
+                let series = self.values.iter().map(|el| el.and_then(T::to_timestamp));
+                let mut series = TemporalSeries::new(series.collect(), None);
+                series.convention = self.convention;
+                Some(SeriesEnum::Timestamp(Box::new(series)))
+            }
+            Codes::Any => {
+                let series = self.values.iter().map(|el| el.map(|_| self.render(el)));
+                Some(SeriesEnum::Any(Box::new(series.collect())))
+            }
+            _ => None,
+        }
+    }
+
+    fn words(&self) -> Words {
+        let mut words = Words::default();
+        self.values
+            .iter()
+            .for_each(|el| words.extend(self.render(el).as_bytes()));
+        words
+    }
+
+    fn take(&self, rows: &[usize]) -> ColumnSlice {
+        let values = rows.iter().map(|&row| {
+            let value = self.values.get(row).copied().flatten();
+            value.map(|value| value.render(self.format.as_deref()))
+        });
+        ColumnSlice::new(values, SliceValues::Text)
+    }
+
+    fn gather(&self, rows: &[usize]) -> SeriesEnum {
+        let values = rows.iter().map(|&row| self.values[row]).collect();
+        let mut series = TemporalSeries::new(values, self.format.as_deref());
+        series.convention = self.convention;
+        T::wrap(series)
+    }
+
+    fn equal_to(&self, other: &dyn SeriesTrait) -> FilterResult<'_> {
+        let set = self.needles(other)?.into_iter().collect::<HashSet<_>>();
+        let ret = self
+            .values
+            .iter()
+            .map(|el| el.is_some_and(|el| set.contains(&el)))
+            .collect();
+
+        Ok(ret)
+    }
+
+    fn compare(&self, other: &dyn SeriesTrait, op: Comparison) -> FilterResult<'_> {
+        let value = self.needles(other)?.first().copied();
+        let ret = self
+            .values
+            .iter()
+            .map(|el| match (el, value) {
+                (Some(el), Some(value)) => op.test(el, &value),
+                _ => false,
+            })
+            .collect();
+
+        Ok(ret)
+    }
+
+    fn date(&self) -> ViewResult<'_, NaiveDate> {
+        T::dates(self)
+    }
+
+    fn value_counts(&self) -> Result<Vec<(Option<String>, usize)>, NonHashable> {
+        let format = self.format.as_deref();
+        let values = self.values.iter().copied();
+        Ok(count_values(values, |value| value.render(format)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::{TemporalValue, Timestamp};
+
+    #[test]
+    fn parse_and_render() {
+        let date = NaiveDate::parse("25/12/2020", None).unwrap();
+        assert_eq!(date.render(None), "2020-12-25");
+        assert_eq!(date.render(Some("%d.%m.%Y")), "25.12.2020");
+        assert_eq!(NaiveDate::parse("12/25/2020", Some("%d/%m/%Y")), None);
+        assert_eq!(
+            NaiveDate::parse("01/02/2020", Some("%d/%m/%Y")),
+            NaiveDate::from_ymd_opt(2020, 2, 1)
+        );
+
+        let time = NaiveTime::parse("9:05", None).unwrap();
+        assert_eq!(time.render(None), "09:05:00");
+
+        let timestamp = Timestamp::parse("2020-01-02T10:00:00+01:30", None).unwrap();
+        assert_eq!(timestamp.offset, Some(5400));
+        assert_eq!(timestamp.utc.to_string(), "2020-01-02 08:30:00");
+        assert_eq!(timestamp.render(None), "2020-01-02T10:00:00+01:30");
+
+        let timestamp = Timestamp::parse("2020-01-02 10:00", None).unwrap();
+        assert_eq!(timestamp.offset, None);
+        assert_eq!(timestamp.render(None), "2020-01-02T10:00:00");
+        assert_eq!(timestamp.render(Some("%H:%M %z")), "");
+    }
+}
@@ -0,0 +1,114 @@
+use bitvec::prelude::{BitVec, Lsb0};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::type_parser::Codes;
+
+/// Values of a slice, integers wider than 32 bits and floats are all widened to `f64`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SliceValues {
+    Boolean(Vec<u8>),
+    Int32(Vec<i32>),
+    Float64(Vec<f64>),
+    Text(Vec<String>),
+}
+
+/// A range of rows of a single column. Nulls hold the default value and are
+/// marked in the validity bitmap, one bit per row with the first row in the lowest bit.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnSlice {
+    pub(crate) dtype: Codes,
+    pub(crate) values: SliceValues,
+    pub(crate) validity: Vec<u8>,
+    pub(crate) len: usize,
+    /// Text of each row when the values alone do not give it back, like formatted numbers.
+    pub(crate) formatted: Option<Vec<String>>,
+}
+
+impl ColumnSlice {
+    pub fn new<T: Default>(
+        values: impl IntoIterator<Item = Option<T>>,
+        wrap: fn(Vec<T>) -> SliceValues,
+    ) -> Self {
+        let values: Vec<Option<T>> = values.into_iter().collect();
+        let validity: BitVec<u8, Lsb0> = values.iter().map(Option::is_some).collect();
+
+        Self {
+            dtype: Codes::Null,
+            len: values.len(),
+            values: wrap(values.into_iter().map(Option::unwrap_or_default).collect()),
+            validity: validity.into_vec(),
+            formatted: None,
+        }
+    }
+
+    pub fn with_dtype(mut self, dtype: Codes) -> Self {
+        self.dtype = dtype;
+        self
+    }
+
+    pub fn with_formatted(mut self, formatted: Vec<String>) -> Self {
+        self.formatted = Some(formatted);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_valid(&self, row: usize) -> bool {
+        self.validity
+            .get(row / 8)
+            .is_some_and(|byte| byte & (1 << (row % 8)) != 0)
+    }
+
+    /// The rows as text, nulls become empty strings.
+    pub fn text(&self) -> Vec<String> {
+        if let Some(formatted) = &self.formatted {
+            return formatted.clone();
+        }
+
+        (0..self.len)
+            .map(|row| {
+                if !self.is_valid(row) {
+                    return String::new();
+                }
+                match &self.values {
+                    SliceValues::Boolean(values) => (values[row] != 0).to_string(),
+                    SliceValues::Int32(values) => values[row].to_string(),
+                    SliceValues::Float64(values) => values[row].to_string(),
+                    SliceValues::Text(values) => values[row].clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Widens through the shortest text of the value, so `0.1f32` stays `0.1`.
+pub fn widen_f32(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(f64::from(value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{widen_f32, ColumnSlice, SliceValues};
+
+    #[test]
+    fn validity() {
+        let values = (0..10).map(|i| (i % 3 != 0).then_some(i));
+        let slice = ColumnSlice::new(values, SliceValues::Int32);
+
+        assert_eq!(slice.len(), 10);
+        assert_eq!(slice.validity, vec![0b1011_0110, 0b0000_0001]);
+        assert_eq!(
+            slice.values,
+            SliceValues::Int32(vec![0, 1, 2, 0, 4, 5, 0, 7, 8, 0])
+        );
+        assert_eq!(slice.text()[..4], ["", "1", "2", ""]);
+        assert_eq!(widen_f32(12.26), 12.26);
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    csv_parser::{last_line, CsvDialect, FieldIter, LineSplitter, LineTerminator},
+    type_parser::{infer_code, Codes, NullTokens},
+};
+
+const DELIMITERS: &[u8] = b",\t;|";
+const QUOTES: &[u8] = b"\"'";
+const SAMPLE_BYTES: usize = 1 << 16;
+const SAMPLE_LINES: usize = 50;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sniffed {
+    pub(crate) dialect: CsvDialect,
+    pub(crate) has_header: bool,
+    pub(crate) confidence: f64,
+}
+
+/// Guesses the dialect of a chunk. The confidence is the share of sampled lines
+/// that split into the most common number of fields, or zero when no candidate
+/// delimiter splits the lines into more than one field.
+pub fn sniff(bytes: &[u8], nulls: &NullTokens) -> Sniffed {
+    let terminator = detect_terminator(bytes);
+    let quote = detect_quote(bytes);
+    let mut best = (CsvDialect::default(), 0.0, 1);
+
+    for &delimiter in DELIMITERS {
+        let dialect = CsvDialect::new(delimiter, quote, None, terminator);
+        let (consistency, modal) = consistency(&sample(bytes, dialect), dialect);
+
+        let (_, best_consistency, best_modal) = best;
+        if (modal > 1, consistency, modal) > (best_modal > 1, best_consistency, best_modal) {
+            best = (dialect, consistency, modal);
+        }
+    }
+
+    let (dialect, consistency, modal) = best;
+    Sniffed {
+        dialect,
+        has_header: has_header(bytes, dialect, nulls),
+        confidence: if modal > 1 { consistency } else { 0.0 },
+    }
+}
+
+/// Votes column by column: a text cell on top of a typed column counts for a
+/// header, a cell of the column's own type counts against. Text-only samples
+/// fall back to checking that the first row is made of unique, unseen names.
+pub fn has_header(bytes: &[u8], dialect: CsvDialect, nulls: &NullTokens) -> bool {
+    let lines = sample(bytes, dialect);
+    let mut rows = lines.iter().map(|line| {
+        FieldIter::new(line, dialect)
+            .map(|word| {
+                if nulls.is_null(&word) {
+                    String::new()
+                } else {
+                    String::from_utf8_lossy(&word).into_owned()
+                }
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let first = match rows.next() {
+        Some(first) => first,
+        None => return false,
+    };
+    let rest: Vec<Vec<String>> = rows.collect();
+
+    let mut votes = 0;
+    for (j, name) in first.iter().enumerate() {
+        let column = rest
+            .iter()
+            .filter_map(|row| row.get(j))
+            .map(|word| infer_code(word))
+            .filter(|&code| code != Codes::Null)
+            .reduce(Codes::promote);
+
+        match (column, infer_code(name)) {
+            (None | Some(Codes::Any), _) | (_, Codes::Null) => {}
+            (Some(_), Codes::Any) => votes += 1,
+            (Some(_), _) => votes -= 1,
+        }
+    }
+
+    if votes != 0 {
+        return votes > 0;
+    }
+
+    first.iter().enumerate().all(|(j, name)| {
+        !name.is_empty()
+            && first.iter().filter(|&other| other == name).count() == 1
+            && rest.iter().all(|row| row.get(j) != Some(name))
+    })
+}
+
+/// Picks the quote candidate found most often right next to a field boundary.
+fn detect_quote(bytes: &[u8]) -> u8 {
+    let window = &bytes[..bytes.len().min(SAMPLE_BYTES)];
+    let is_boundary = |byte: Option<&u8>| {
+        byte.is_none_or(|byte| DELIMITERS.contains(byte) || *byte == b'\n' || *byte == b'\r')
+    };
+
+    QUOTES
+        .iter()
+        .copied()
+        .rev()
+        .max_by_key(|&quote| {
+            (0..window.len())
+                .filter(|&i| window[i] == quote)
+                .filter(|&i| {
+                    is_boundary(i.checked_sub(1).and_then(|j| window.get(j)))
+                        || is_boundary(window.get(i + 1))
+                })
+                .count()
+        })
+        .unwrap_or(b'"')
+}
+
+fn detect_terminator(bytes: &[u8]) -> LineTerminator {
+    let (mut crlf, mut cr, mut lf) = (0, 0, 0);
+    let mut iter = bytes[..bytes.len().min(SAMPLE_BYTES)].iter().peekable();
+
+    while let Some(&byte) = iter.next() {
+        match (byte, iter.peek()) {
+            (b'\r', Some(b'\n')) => {
+                iter.next();
+                crlf += 1;
+            }
+            (b'\r', Some(_)) => cr += 1,
+            (b'\n', _) => lf += 1,
+            _ => {}
+        }
+    }
+
+    match (crlf, cr, lf) {
+        (0, 0, 0) => LineTerminator::Any,
+        (_, 0, 0) => LineTerminator::CrLf,
+        (0, _, 0) => LineTerminator::Cr,
+        (0, 0, _) => LineTerminator::Lf,
+        _ => LineTerminator::Any,
+    }
+}
+
+/// Complete lines at the start of the chunk, the trailing one may be cut short.
+fn sample(bytes: &[u8], dialect: CsvDialect) -> Vec<&[u8]> {
+    let window = &bytes[..bytes.len().min(SAMPLE_BYTES)];
+    let complete = &window[..window.len() - last_line(window, dialect).len()];
+    let complete = if complete.is_empty() {
+        window
+    } else {
+        complete
+    };
+
+    LineSplitter::new(complete, dialect)
+        .take(SAMPLE_LINES)
+        .collect()
+}
+
+fn consistency(lines: &[&[u8]], dialect: CsvDialect) -> (f64, usize) {
+    let mut frequencies: HashMap<usize, usize> = HashMap::new();
+    for line in lines {
+        *frequencies
+            .entry(FieldIter::new(line, dialect).count())
+            .or_default() += 1;
+    }
+
+    frequencies
+        .into_iter()
+        .max_by_key(|&(fields, n)| (n, fields))
+        .map_or((0.0, 1), |(fields, n)| {
+            (n as f64 / lines.len() as f64, fields)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{has_header, sniff};
+    use crate::{
+        csv_parser::{CsvDialect, LineTerminator},
+        type_parser::NullTokens,
+    };
+
+    #[test]
+    fn sniff_dialect() {
+        let data = "Name;Type;Attack\r\nFlareon;Fire;130\r\nJolteon;Electric;65\r\nVapor";
+        let sniffed = sniff(data.as_bytes(), &NullTokens::default());
+
+        assert_eq!(
+            sniffed.dialect,
+            CsvDialect::new(b';', b'"', None, LineTerminator::CrLf)
+        );
+        assert!(sniffed.has_header);
+        assert_eq!(sniffed.confidence, 1.0);
+
+        let data = "Flareon\t'Fire\tNormal'\t130\nVaporeon\tWater\t65\nEspeon\tPsychic\t65\n";
+        let sniffed = sniff(data.as_bytes(), &NullTokens::default());
+
+        assert_eq!(sniffed.dialect.delimiter, b'\t');
+        assert_eq!(sniffed.dialect.quote, b'\'');
+        assert_eq!(sniffed.dialect.terminator, LineTerminator::Lf);
+        assert!(!sniffed.has_header);
+    }
+
+    #[test]
+    fn header() {
+        let dialect = CsvDialect::default();
+        let nulls = NullTokens::default();
+
+        assert!(has_header(
+            "Name,Type\nFlareon,Fire\nJolteon,Electric\n".as_bytes(),
+            dialect,
+            &nulls
+        ));
+        assert!(!has_header(
+            "Flareon,Fire\nJolteon,Fire\nVaporeon,Water\n".as_bytes(),
+            dialect,
+            &nulls
+        ));
+        assert!(!has_header(
+            "1,2.5\n2,3.5\n3,4.5\n".as_bytes(),
+            dialect,
+            &nulls
+        ));
+        assert!(has_header(
+            "Name,Attack\nFlareon,NA\nJolteon,65\n".as_bytes(),
+            dialect,
+            &nulls
+        ));
+    }
+}
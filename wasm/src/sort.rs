@@ -0,0 +1,128 @@
+use core::cmp::Ordering;
+
+use bitvec::prelude::BitVec;
+
+use crate::column::Column;
+
+/// One column of a sort, nulls go last unless asked otherwise whatever the direction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortKey {
+    pub(crate) column: String,
+    pub(crate) descending: bool,
+    pub(crate) nulls_first: bool,
+}
+
+impl SortKey {
+    pub fn new(column: &str, descending: bool, nulls_first: bool) -> Self {
+        Self {
+            column: column.into(),
+            descending,
+            nulls_first,
+        }
+    }
+
+    /// `Attack DESC NULLS FIRST`, the direction and null placement are optional.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (text, nulls_first) = match text.strip_suffix(" NULLS FIRST") {
+            Some(text) => (text, true),
+            None => (text.strip_suffix(" NULLS LAST").unwrap_or(text), false),
+        };
+        let (column, descending) = match text.strip_suffix(" DESC") {
+            Some(column) => (column, true),
+            None => (text.strip_suffix(" ASC").unwrap_or(text), false),
+        };
+
+        let column = column.trim();
+        (!column.is_empty()).then(|| Self::new(column, descending, nulls_first))
+    }
+}
+
+/// Order of two rows by the keys, keys naming no column are ignored.
+fn comparator<'a>(
+    columns: &'a [Column],
+    keys: &'a [SortKey],
+) -> impl Fn(usize, usize) -> Ordering + 'a {
+    let keys: Vec<(&Column, &SortKey, BitVec)> = keys
+        .iter()
+        .filter_map(|key| {
+            let column = columns.iter().find(|column| column.name() == key.column)?;
+            Some((column, key, column.null_mask()))
+        })
+        .collect();
+
+    move |a, b| {
+        keys.iter()
+            .fold(Ordering::Equal, |ord, (column, key, nulls)| {
+                ord.then_with(|| match (nulls[a], nulls[b]) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) if key.nulls_first => Ordering::Less,
+                    (true, false) => Ordering::Greater,
+                    (false, true) if key.nulls_first => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) if key.descending => column.series().cmp_rows(a, b).reverse(),
+                    (false, false) => column.series().cmp_rows(a, b),
+                })
+            })
+    }
+}
+
+/// Stable permutation of the rows ordered by the keys, keys naming no column are ignored.
+pub fn sorted_index(columns: &[Column], keys: &[SortKey]) -> Vec<usize> {
+    merge_index(columns, keys, &[], 0)
+}
+
+/// Sorts the rows from `from` on and merges them into `index`, already sorted over the
+/// rows before. Ties keep the earlier row first, as a stable sort of every row would.
+pub fn merge_index(
+    columns: &[Column],
+    keys: &[SortKey],
+    index: &[usize],
+    from: usize,
+) -> Vec<usize> {
+    let height = columns.first().map_or(0, Column::len);
+    let cmp = comparator(columns, keys);
+
+    let mut rows: Vec<usize> = (from..height).collect();
+    rows.sort_by(|&a, &b| cmp(a, b));
+
+    let mut merged = Vec::with_capacity(height);
+    let (mut old, mut new) = (
+        index.iter().copied().peekable(),
+        rows.into_iter().peekable(),
+    );
+    while let (Some(&a), Some(&b)) = (old.peek(), new.peek()) {
+        if cmp(b, a) == Ordering::Less {
+            merged.push(b);
+            new.next();
+        } else {
+            merged.push(a);
+            old.next();
+        }
+    }
+    merged.extend(old);
+    merged.extend(new);
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::SortKey;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            SortKey::parse(" Type 1 "),
+            Some(SortKey::new("Type 1", false, false))
+        );
+        assert_eq!(
+            SortKey::parse("Attack DESC NULLS FIRST"),
+            Some(SortKey::new("Attack", true, true))
+        );
+        assert_eq!(
+            SortKey::parse("Attack ASC NULLS LAST"),
+            Some(SortKey::new("Attack", false, false))
+        );
+        assert_eq!(SortKey::parse("  "), None);
+    }
+}
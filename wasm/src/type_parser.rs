@@ -1,5 +1,13 @@
-use crate::{series::Numeric, Words};
+use crate::{
+    diagnostics::SchemaError,
+    series::{
+        temporal::{TemporalValue, Timestamp},
+        Numeric,
+    },
+    Words,
+};
 
+use chrono::{NaiveDate, NaiveTime};
 use js_sys::JsString;
 use lazy_static::lazy_static;
 use lexical::{parse, FromLexical};
@@ -7,7 +15,7 @@ use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 
 #[repr(usize)]
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Deserialize, Serialize)]
 pub enum Codes {
     Null = 0,
     Boolean = 1,
@@ -16,16 +24,96 @@ pub enum Codes {
     Int128 = 4,
     Float32 = 5,
     Float64 = 6,
-    Any = 7,
+    Date = 7,
+    Time = 8,
+    Timestamp = 9,
+    Any = 10,
     TmpInt = 99,
     TmpFloat = 100,
 }
 
+impl Codes {
+    /// Smallest type able to hold the values of both types.
+    pub fn promote(self, other: Codes) -> Codes {
+        match (self.min(other), self.max(other)) {
+            (Codes::Null, code) => code,
+            (lower, upper) if lower == upper => lower,
+            (Codes::Int32 | Codes::Int64, upper @ (Codes::Int64 | Codes::Int128)) => upper,
+            (
+                Codes::Int32 | Codes::Int64 | Codes::Int128 | Codes::Float32,
+                Codes::Float32 | Codes::Float64,
+            ) => Codes::Float64,
+            (Codes::Date, Codes::Timestamp) => Codes::Timestamp,
+            _ => Codes::Any,
+        }
+    }
+
+    pub fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Codes::Int32 | Codes::Int64 | Codes::Int128 | Codes::Float32 | Codes::Float64
+        )
+    }
+}
+
+impl TryFrom<&str> for Codes {
+    type Error = SchemaError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        match name {
+            "Boolean" => Ok(Codes::Boolean),
+            "Int32" => Ok(Codes::Int32),
+            "Int64" => Ok(Codes::Int64),
+            "Int128" => Ok(Codes::Int128),
+            "Float32" => Ok(Codes::Float32),
+            "Float64" => Ok(Codes::Float64),
+            "Date" => Ok(Codes::Date),
+            "Time" => Ok(Codes::Time),
+            "Timestamp" => Ok(Codes::Timestamp),
+            "Any" | "String" => Ok(Codes::Any),
+            _ => Err(SchemaError::UnknownType(name.into())),
+        }
+    }
+}
+
+/// Words read as missing values, on top of the empty word.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NullTokens(Vec<Vec<u8>>);
+
+impl Default for NullTokens {
+    fn default() -> Self {
+        Self::new(["NA", "null", "-", "NaN", "#N/A"])
+    }
+}
+
+impl NullTokens {
+    pub fn new<T: AsRef<[u8]>>(tokens: impl IntoIterator<Item = T>) -> Self {
+        Self(
+            tokens
+                .into_iter()
+                .map(|token| token.as_ref().to_vec())
+                .collect(),
+        )
+    }
+
+    pub fn is_null(&self, word: &[u8]) -> bool {
+        let word = word.trim_ascii();
+        word.is_empty() || self.0.iter().any(|token| token == word)
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.iter().map(Vec::as_slice)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum StageOne<'a> {
     Int(&'a str),
     Float(&'a str),
     Boolean(&'a str),
+    Date(&'a str),
+    Time(&'a str),
+    Timestamp(&'a str),
     Any(&'a str),
 }
 
@@ -35,6 +123,9 @@ impl<'a> From<StageOne<'a>> for Codes {
             StageOne::Float(_) => Codes::TmpFloat,
             StageOne::Int(_) => Codes::TmpInt,
             StageOne::Boolean(_) => Codes::Boolean,
+            StageOne::Date(_) => Codes::Date,
+            StageOne::Time(_) => Codes::Time,
+            StageOne::Timestamp(_) => Codes::Timestamp,
             StageOne::Any(_) => Codes::Any,
         }
     }
@@ -49,7 +140,11 @@ impl From<Codes> for JsString {
             Codes::Int128 => JsString::from("Int128"),
             Codes::Float32 => JsString::from("Float32"),
             Codes::Float64 => JsString::from("Float64"),
+            Codes::Date => JsString::from("Date"),
+            Codes::Time => JsString::from("Time"),
+            Codes::Timestamp => JsString::from("Timestamp"),
             Codes::Any => JsString::from("Any"),
+            Codes::Null => JsString::from("Null"),
             _ => JsString::from("Unknown"),
         }
     }
@@ -97,20 +192,32 @@ impl From<FloatTypes> for Codes {
 
 impl From<&str> for FloatTypes {
     fn from(cell: &str) -> FloatTypes {
-        cell.parse::<f32>()
-            .map(FloatTypes::Float32)
-            .or_else(|_| cell.parse::<f64>().map(FloatTypes::Float64))
-            .expect("Float overflow")
+        let wide = cell.parse::<f64>().expect("Float overflow");
+
+        // Single precision is kept only when it neither overflows nor underflows
+        match cell.parse::<f32>() {
+            Ok(narrow) if narrow.is_finite() && (narrow == 0.0) == (wide == 0.0) => {
+                FloatTypes::Float32(narrow)
+            }
+            _ => FloatTypes::Float64(wide),
+        }
     }
 }
 
 lazy_static! {
-    static ref FLOAT: Regex = Regex::new(r"^\s*-?(\d*\.\d+)$").unwrap();
+    static ref FLOAT: Regex =
+        Regex::new(r"^\s*[-+]?(\d*\.\d+|\d+\.\d*|\d+(\.\d*)?[eE][-+]?\d+|\.\d+[eE][-+]?\d+)$").unwrap();
     static ref INTEGER: Regex = Regex::new(r"^\s*-?(\d+)$").unwrap();
     static ref BOOL: Regex = RegexBuilder::new(r"^\s*(true)$|^(false)$")
         .case_insensitive(true)
         .build()
         .unwrap();
+    static ref DATE: Regex = Regex::new(r"^\s*(\d{4}-\d{2}-\d{2}|\d{1,2}/\d{1,2}/\d{4})\s*$").unwrap();
+    static ref TIME: Regex = Regex::new(r"^\s*\d{1,2}:\d{2}(:\d{2}(\.\d+)?)?\s*$").unwrap();
+    static ref TIMESTAMP: Regex = Regex::new(
+        r"^\s*(\d{4}-\d{2}-\d{2}|\d{1,2}/\d{1,2}/\d{4})[T ]\d{1,2}:\d{2}(:\d{2}(\.\d+)?)?(Z|[+-]\d{2}:?\d{2})?\s*$"
+    )
+    .unwrap();
 }
 
 #[allow(clippy::needless_lifetimes)]
@@ -121,11 +228,34 @@ pub fn first_phase<'a>(word: &'a str) -> StageOne {
         StageOne::Int(word)
     } else if BOOL.is_match(word) {
         StageOne::Boolean(word)
+    } else if DATE.is_match(word) {
+        StageOne::Date(word)
+    } else if TIME.is_match(word) {
+        StageOne::Time(word)
+    } else if TIMESTAMP.is_match(word) {
+        StageOne::Timestamp(word)
     } else {
         StageOne::Any(word)
     }
 }
 
+pub fn infer_code(word: &str) -> Codes {
+    match first_phase(word) {
+        StageOne::Int(text) if text.trim().parse::<i128>().is_err() => Codes::Float64,
+        StageOne::Int(text) => IntegerTypes::from(text.trim()).into(),
+        StageOne::Float(text) => FloatTypes::from(text.trim()).into(),
+        StageOne::Date(text) if NaiveDate::parse(text, None).is_none() => Codes::Any,
+        StageOne::Time(text) if NaiveTime::parse(text, None).is_none() => Codes::Any,
+        StageOne::Timestamp(text) if Timestamp::parse(text, None).is_none() => Codes::Any,
+        StageOne::Any("") => Codes::Null,
+        val => val.into(),
+    }
+}
+
+pub fn infer_bytes(bytes: &[u8]) -> Codes {
+    std::str::from_utf8(bytes).map_or(Codes::Any, infer_code)
+}
+
 pub fn bytes_to_bool(bytes: &[u8]) -> Option<bool> {
     if bytes.eq_ignore_ascii_case(b"true") || bytes.eq_ignore_ascii_case(b"\"true\"") {
         Some(true)
@@ -157,7 +287,10 @@ pub fn parse_bool(words: Words) -> Vec<Option<bool>> {
 pub fn parse_utf8(words: Words) -> Vec<Option<String>> {
     let mut ret = Vec::new();
     words.into_iter().for_each(|bytes| {
-        let el = String::from_utf8(bytes.into()).ok();
+        let el = match bytes {
+            [] => None,
+            bytes => String::from_utf8(bytes.into()).ok(),
+        };
         ret.push(el);
     });
     ret
@@ -33,7 +33,10 @@ pub trait LendingIterator {
 
 #[allow(clippy::needless_lifetimes)]
 impl<'a, T> LendingIterator for HeaderFillerGenerator<'a, T> {
-    type Item<'t> = &'t [u8] where Self: 't;
+    type Item<'t>
+        = &'t [u8]
+    where
+        Self: 't;
 
     fn next<'t>(&'t mut self) -> Option<Self::Item<'t>> {
         if self.cycles > self.symbols.len() {
@@ -46,10 +49,12 @@ impl<'a, T> LendingIterator for HeaderFillerGenerator<'a, T> {
         self.cursor += 1;
         self.cursor %= self.symbols.len();
         if self.cursor % self.symbols.len() == 0 {
-            let new_symbol = self.symbols[self.cycles][1];
-            self.symbols
-                .iter_mut()
-                .for_each(|symbol| symbol[0] = new_symbol);
+            // Past the last prefix there is nothing left to generate
+            if let Some(&[_, new_symbol]) = self.symbols.get(self.cycles) {
+                self.symbols
+                    .iter_mut()
+                    .for_each(|symbol| symbol[0] = new_symbol);
+            }
             self.cycles += 1;
         }
         Some(&self.current[0..n])
@@ -91,6 +96,28 @@ impl<'a, T> Default for HeaderFillerGenerator<'a, T> {
     }
 }
 
+/// Position of a name given by the default generator, `A` is 0 and `AA` comes after `Z`.
+pub fn header_position(name: &[u8]) -> Option<usize> {
+    let mut filler = HeaderFillerGenerator::<u8>::default();
+    let mut position = 0;
+    while let Some(header) = filler.next() {
+        if header == name {
+            return Some(position);
+        }
+        position += 1;
+    }
+    None
+}
+
+pub fn header_name(position: usize) -> Option<String> {
+    let mut filler = HeaderFillerGenerator::<u8>::default();
+    for _ in 0..position {
+        filler.next()?;
+    }
+    let name = filler.next()?;
+    Some(String::from_utf8_lossy(name).into_owned())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -109,4 +136,15 @@ mod test {
         assert_eq!(Some(&b'A'), bytes.get(26));
         assert_eq!(Some(&b'A'), bytes.get(27));
     }
+
+    #[test]
+    fn header_positions() {
+        assert_eq!(header_position(b"A"), Some(0));
+        assert_eq!(header_position(b"AA"), Some(26));
+        assert_eq!(header_position(b"BC"), Some(54));
+        assert_eq!(header_position(b"ZZ"), Some(701));
+        assert_eq!(header_position(b"AAA"), None);
+        assert_eq!(header_name(54).as_deref(), Some("BC"));
+        assert_eq!(header_name(702), None);
+    }
 }